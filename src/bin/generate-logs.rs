@@ -0,0 +1,82 @@
+//! Generates `data/logs.json` from GTFO's datablocks
+//!
+//! `generate-logs <datablock dir> [--output <path>] [--dataset-version <label>]`
+
+use anyhow::{anyhow, Context, Result};
+use gtfo_log_tracker::datablocks;
+use gtfo_log_tracker::game_data::StoryLog;
+use serde::Serialize;
+use simplelog::{ColorChoice, ConfigBuilder, LevelFilter, TermLogger, TerminalMode};
+use std::path::PathBuf;
+use std::{env, fs};
+
+const USAGE: &str =
+    "Usage: generate-logs <datablock dir> [--output <path>] [--dataset-version <label>]";
+
+struct Args {
+    dir: PathBuf,
+    output: Option<PathBuf>,
+    dataset_version: Option<String>,
+}
+
+#[derive(Serialize)]
+struct VersionedDataset<'a> {
+    version: &'a str,
+    logs: &'a [StoryLog],
+}
+
+fn main() -> Result<()> {
+    TermLogger::init(
+        LevelFilter::Info,
+        ConfigBuilder::new().build(),
+        TerminalMode::Stderr,
+        ColorChoice::Auto,
+    )?;
+
+    let args = parse_args(env::args().skip(1))?;
+
+    let logs = datablocks::generate_logs(&args.dir)?;
+    log::info!("Found {} logs", logs.len());
+
+    let json = match &args.dataset_version {
+        Some(version) => serde_json::to_string_pretty(&VersionedDataset {
+            version,
+            logs: &logs,
+        }),
+        None => serde_json::to_string_pretty(&logs),
+    }?;
+
+    match &args.output {
+        Some(path) => fs::write(path, json + "\n")
+            .with_context(|| format!("Couldn't write to '{}'", path.display()))?,
+        None => println!("{json}"),
+    }
+
+    Ok(())
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args> {
+    let mut dir = None;
+    let mut output = None;
+    let mut dataset_version = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow!("{arg} requires a value\n{USAGE}"))
+        };
+        match arg.as_str() {
+            "--output" => output = Some(PathBuf::from(value()?)),
+            "--dataset-version" => dataset_version = Some(value()?),
+            _ if arg.starts_with("--") => return Err(anyhow!("Unknown option '{arg}'\n{USAGE}")),
+            _ if dir.is_none() => dir = Some(PathBuf::from(arg)),
+            _ => return Err(anyhow!("Unexpected argument '{arg}'\n{USAGE}")),
+        }
+    }
+
+    Ok(Args {
+        dir: dir.ok_or_else(|| anyhow!(USAGE))?,
+        output,
+        dataset_version,
+    })
+}
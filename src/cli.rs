@@ -0,0 +1,410 @@
+use crate::dataset_diff::{self, Change};
+use crate::export::{export, export_to_file, ExportFormat};
+use crate::game_data::{load_dataset, map_log_to_rows, StoryLog};
+use crate::level_id::LevelId;
+use crate::progress::ProgressStore;
+use crate::{config, read_logs, LoadedLogs, Options};
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+use time::format_description::BorrowedFormatItem;
+use time::macros::format_description;
+
+/// Subcommands that print progress to the terminal instead of launching the GUI
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Status,
+    List {
+        unread: bool,
+        level: Option<LevelId>,
+    },
+    Show(String),
+    Export {
+        format: ExportFormat,
+        output: Option<PathBuf>,
+    },
+    /// Compares two logs.json datasets
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+    },
+}
+
+impl Command {
+    /// Whether the command needs the game's data path and read progress
+    pub fn uses_progress(&self) -> bool {
+        !matches!(self, Command::Diff { .. })
+    }
+}
+
+const FIRST_SEEN_FORMAT: &[BorrowedFormatItem] =
+    format_description!("[year]-[month]-[day] [hour]:[minute] UTC");
+
+/// Command options that are followed by a value and so can't be mistaken for a subcommand
+const VALUE_OPTIONS: [&str; 2] = ["--level", "--output"];
+
+/// Returns the subcommand in `args`, if there is one. `args` should include the program name.
+pub fn parse_command(args: &[String]) -> Result<Option<Command>> {
+    let positional = positional_args(args);
+    let option_value = |name: &str| {
+        args.iter()
+            .position(|s| s == name)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+
+    let command = match positional.first().copied() {
+        None => return Ok(None),
+        Some("status") => Command::Status,
+        Some("list") => Command::List {
+            unread: args.iter().any(|s| s == "--unread"),
+            level: option_value("--level").map(|l| l.parse()).transpose()?,
+        },
+        Some("show") => Command::Show(
+            positional
+                .get(1)
+                .map(|s| s.to_string())
+                .ok_or_else(|| anyhow!("show requires a log id or name"))?,
+        ),
+        Some("export") => Command::Export {
+            format: positional
+                .get(1)
+                .ok_or_else(|| anyhow!("export requires a format"))?
+                .parse()?,
+            output: option_value("--output").map(PathBuf::from),
+        },
+        Some("diff") => match positional[1..] {
+            [old, new] => Command::Diff {
+                old: PathBuf::from(old),
+                new: PathBuf::from(new),
+            },
+            _ => return Err(anyhow!("diff requires an old and a new logs.json")),
+        },
+        Some(other) => return Err(anyhow!("Unknown command '{other}'")),
+    };
+
+    Ok(Some(command))
+}
+
+fn positional_args(args: &[String]) -> Vec<&str> {
+    let mut positional = Vec::new();
+    let mut iter = args.iter().skip(1);
+
+    while let Some(arg) = iter.next() {
+        if VALUE_OPTIONS.contains(&arg.as_str()) || config::VALUE_ARGS.contains(&arg.as_str()) {
+            iter.next();
+        } else if !arg.starts_with("--") {
+            positional.push(arg.as_str());
+        }
+    }
+
+    positional
+}
+
+pub fn run(command: Command, options: Options) -> Result<()> {
+    let output = match command {
+        Command::Status => {
+            let (all_logs, progress) = load_progress(options)?;
+            status(&all_logs, &progress.read_ids())
+        }
+        Command::List { unread, level } => {
+            let (all_logs, progress) = load_progress(options)?;
+            list(&all_logs, &progress.read_ids(), unread, level)
+        }
+        Command::Show(query) => {
+            let (all_logs, progress) = load_progress(options)?;
+            show(&all_logs, &progress, &query)?
+        }
+        Command::Export { format, output } => {
+            let (all_logs, progress) = load_progress(options)?;
+            match output {
+                Some(path) => {
+                    export_to_file(&all_logs, &progress.read_ids(), format, &path)?;
+                    format!("Exported to '{}'\n", path.display())
+                }
+                None => export(&all_logs, &progress.read_ids(), format)?,
+            }
+        }
+        Command::Diff { old, new } => {
+            let old = load_dataset(Some(&old))?;
+            let new = load_dataset(Some(&new))?;
+            diff_report(&dataset_diff::diff(&old.logs, &new.logs))
+        }
+    };
+    print!("{output}");
+
+    Ok(())
+}
+
+fn load_progress(options: Options) -> Result<(Vec<StoryLog>, ProgressStore)> {
+    let LoadedLogs {
+        dataset, progress, ..
+    } = read_logs(options)?;
+
+    Ok((dataset.logs, progress))
+}
+
+fn status(all_logs: &[StoryLog], read_log_ids: &HashSet<u32>) -> String {
+    let mut rundowns: BTreeMap<u8, (usize, usize)> = BTreeMap::new();
+    for log in all_logs {
+        let rundown_ids: HashSet<u8> = log.locations.iter().map(|l| l.level.rundown).collect();
+        for rundown in rundown_ids {
+            let (read, total) = rundowns.entry(rundown).or_default();
+            *total += 1;
+            if read_log_ids.contains(&log.id) {
+                *read += 1;
+            }
+        }
+    }
+
+    let read = all_logs
+        .iter()
+        .filter(|l| read_log_ids.contains(&l.id))
+        .count();
+    let mut output = format!("{}/{} Read\n", read, all_logs.len());
+    for (rundown, (read, total)) in rundowns {
+        let _ = writeln!(output, "  R{rundown:<3} {read}/{total}");
+    }
+
+    output
+}
+
+/// Lists each change, marking the ones that alter progress counts with `!`
+fn diff_report(changes: &[Change]) -> String {
+    if changes.is_empty() {
+        return "No changes\n".to_string();
+    }
+
+    let mut output = changes.iter().fold(String::new(), |mut output, change| {
+        let marker = if change.affects_progress() { '!' } else { ' ' };
+        let _ = writeln!(output, "{marker} {change}");
+        output
+    });
+    let _ = writeln!(
+        output,
+        "{} changes, {} affecting progress counts (marked with !)",
+        changes.len(),
+        changes.iter().filter(|c| c.affects_progress()).count()
+    );
+
+    output
+}
+
+fn list(
+    all_logs: &[StoryLog],
+    read_log_ids: &HashSet<u32>,
+    unread: bool,
+    level: Option<LevelId>,
+) -> String {
+    all_logs
+        .iter()
+        .flat_map(|l| map_log_to_rows(l, read_log_ids))
+        .filter(|r| !(unread && r.read))
+        .filter(|r| level.is_none() || level == Some(r.level))
+        .fold(String::new(), |mut output, r| {
+            let read = if r.read { "[x]" } else { "[ ]" };
+            let _ = writeln!(
+                output,
+                "{read} {:<6} {:<12} {:<14} {}",
+                r.level, r.zone, r.name, r.id
+            );
+            output
+        })
+}
+
+fn show(all_logs: &[StoryLog], progress: &ProgressStore, query: &str) -> Result<String> {
+    let id = query.parse::<u32>().ok();
+    let matches: Vec<&StoryLog> = all_logs
+        .iter()
+        .filter(|log| {
+            Some(log.id) == id
+                || log
+                    .locations
+                    .iter()
+                    .any(|l| l.name.eq_ignore_ascii_case(query))
+        })
+        .collect();
+
+    if matches.is_empty() {
+        return Err(anyhow!("No log found matching '{query}'"));
+    }
+
+    let mut output = String::new();
+    for log in matches {
+        let _ = writeln!(output, "Id: {}", log.id);
+        match progress.get(log.id) {
+            Some(record) => {
+                let _ = writeln!(
+                    output,
+                    "Read: yes (first seen {} via {:?}{})",
+                    record
+                        .first_seen
+                        .format(FIRST_SEEN_FORMAT)
+                        .unwrap_or_default(),
+                    record.source,
+                    record
+                        .level
+                        .as_ref()
+                        .map(|l| format!(" in {l}"))
+                        .unwrap_or_default()
+                );
+            }
+            None => {
+                let _ = writeln!(output, "Read: no");
+            }
+        }
+        for row in map_log_to_rows(log, &HashSet::new()) {
+            let _ = write!(output, "  {} zone {} - {}", row.level, row.zone, row.name);
+            if let Some(audio_id) = row.audio_id {
+                let _ = write!(output, " (audio {audio_id})");
+            }
+            let _ = writeln!(output);
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    mod parse_command {
+        use crate::cli::{parse_command, Command};
+        use crate::export::ExportFormat;
+        use crate::level_id::LevelId;
+        use std::path::PathBuf;
+
+        fn args(s: &str) -> Vec<String> {
+            s.split_whitespace().map(String::from).collect()
+        }
+
+        #[test]
+        fn no_command_runs_gui() {
+            let command = parse_command(&args("tracker --playfab")).unwrap();
+
+            assert_eq!(command, None);
+        }
+
+        #[test]
+        fn skips_data_path_value() {
+            let command = parse_command(&args("tracker --data-path /a/path status")).unwrap();
+
+            assert_eq!(command, Some(Command::Status));
+        }
+
+        #[test]
+        fn parses_list_options() {
+            let command = parse_command(&args("tracker list --unread --level R7D1")).unwrap();
+
+            assert_eq!(
+                command,
+                Some(Command::List {
+                    unread: true,
+                    level: Some(LevelId::new(7, 'D', 1))
+                })
+            );
+        }
+
+        #[test]
+        fn parses_show_query() {
+            let command = parse_command(&args("tracker show 2MD-N3H-SYH")).unwrap();
+
+            assert_eq!(command, Some(Command::Show("2MD-N3H-SYH".to_string())));
+        }
+
+        #[test]
+        fn parses_export_options() {
+            let command = parse_command(&args("tracker export csv --output out.csv")).unwrap();
+
+            assert_eq!(
+                command,
+                Some(Command::Export {
+                    format: ExportFormat::Csv,
+                    output: Some(PathBuf::from("out.csv"))
+                })
+            );
+        }
+
+        #[test]
+        fn parses_diff_paths() {
+            let command = parse_command(&args("tracker diff old.json new.json")).unwrap();
+
+            assert_eq!(
+                command,
+                Some(Command::Diff {
+                    old: PathBuf::from("old.json"),
+                    new: PathBuf::from("new.json")
+                })
+            );
+        }
+
+        #[test]
+        fn diff_requires_two_paths() {
+            let result = parse_command(&args("tracker diff old.json"));
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn show_requires_query() {
+            let result = parse_command(&args("tracker show"));
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn rejects_unknown_command() {
+            let result = parse_command(&args("tracker bogus"));
+
+            assert!(result.is_err());
+        }
+    }
+    mod status {
+        use crate::cli::status;
+        use crate::test_support::story_log;
+        use std::collections::HashSet;
+
+        #[test]
+        fn counts_per_rundown() {
+            let logs = vec![
+                story_log(1, "AAA-BBB-CCC", &["R1A1"]),
+                story_log(2, "AAA-BBB-CCC", &["R1A1"]),
+                story_log(3, "AAA-BBB-CCC", &["R2A1"]),
+            ];
+
+            let output = status(&logs, &HashSet::from([1, 3]));
+
+            assert_eq!(output, "2/3 Read\n  R1   1/2\n  R2   1/1\n");
+        }
+    }
+    mod diff_report {
+        use crate::cli::diff_report;
+        use crate::dataset_diff::Change;
+        use crate::level_id::LevelId;
+
+        #[test]
+        fn marks_progress_changes() {
+            let changes = vec![
+                Change::Added {
+                    id: 1,
+                    name: "AAA (R1A1)".to_string(),
+                },
+                Change::Moved {
+                    id: 2,
+                    name: "BBB".to_string(),
+                    level: LevelId::new(1, 'A', 1),
+                    old_zones: vec![1],
+                    new_zones: vec![2],
+                },
+            ];
+
+            let output = diff_report(&changes);
+
+            assert_eq!(
+                output,
+                "! added 1 AAA (R1A1)\n  moved 2 BBB in R1A1 from zones [1] to [2]\n\
+                 2 changes, 1 affecting progress counts (marked with !)\n"
+            );
+        }
+    }
+}
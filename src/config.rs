@@ -0,0 +1,569 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Where read logs are loaded from on startup
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DataSource {
+    #[default]
+    Logs,
+    PlayFab,
+    /// PlayFab, or its saved snapshot, with reads from log files added on top
+    Hybrid,
+}
+
+/// How the game's log directory is watched for changes while the GUI is open
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchMode {
+    /// Use the OS's file change notifications
+    #[default]
+    Events,
+    /// Check for changes on an interval for file systems without change notifications
+    Poll,
+    Off,
+}
+
+/// Fully resolved tracker options
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub gtfo_path: PathBuf,
+    pub data_source: DataSource,
+    pub watch_mode: WatchMode,
+    pub poll_interval: Duration,
+    /// Only set if configured, otherwise the GUI uses what was last chosen in it
+    pub hide_read: Option<bool>,
+    pub auto_filter: Option<bool>,
+    pub playfab_timeout: Duration,
+    pub playfab_connect_timeout: Duration,
+    /// How many times a PlayFab request is retried after a network or server error
+    pub playfab_retries: u32,
+    /// PlayFab API to use instead of GTFO's, such as a local stand-in for testing
+    pub playfab_base_url: Option<String>,
+    /// Story log dataset to use instead of the built-in one
+    pub logs_data: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            gtfo_path: PathBuf::new(),
+            data_source: DataSource::default(),
+            watch_mode: WatchMode::default(),
+            poll_interval: Duration::from_secs(2),
+            hide_read: None,
+            auto_filter: None,
+            playfab_timeout: Duration::from_secs(30),
+            playfab_connect_timeout: Duration::from_secs(10),
+            playfab_retries: 2,
+            playfab_base_url: None,
+            logs_data: None,
+        }
+    }
+}
+
+/// One layer of configuration. Unset values fall through to the layer below.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, rename_all = "snake_case", deny_unknown_fields)]
+pub struct Config {
+    pub data_path: Option<PathBuf>,
+    pub data_source: Option<DataSource>,
+    pub watch_mode: Option<WatchMode>,
+    pub poll_interval_secs: Option<u64>,
+    pub logs_data: Option<PathBuf>,
+    pub ui: UiConfig,
+    pub playfab: PlayFabConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    pub hide_read: Option<bool>,
+    pub auto_filter: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayFabConfig {
+    pub timeout_secs: Option<u64>,
+    pub connect_timeout_secs: Option<u64>,
+    pub retries: Option<u32>,
+    pub base_url: Option<String>,
+}
+
+const ENV_PREFIX: &str = "GTFO_LOG_TRACKER_";
+
+/// Command line options that take a value
+pub const VALUE_ARGS: [&str; 12] = [
+    "--config",
+    "--data-path",
+    "--data-source",
+    "--watch",
+    "--poll-interval-secs",
+    "--logs-data",
+    "--hide-read",
+    "--auto-filter",
+    "--playfab-timeout-secs",
+    "--playfab-connect-timeout-secs",
+    "--playfab-retries",
+    "--playfab-base-url",
+];
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("gtfo-log-tracker").join("config.toml"))
+    }
+
+    /// Loads the config file at `path`. A missing file is treated as an empty config.
+    pub fn load(path: &Path) -> Result<Config> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Couldn't read config file '{}'", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file '{}'", path.display()))
+    }
+
+    /// Reads `GTFO_LOG_TRACKER_*` variables using `var` to look them up
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Result<Config> {
+        let get = |name: &str| var(&format!("{ENV_PREFIX}{name}"));
+
+        Ok(Config {
+            data_path: get("DATA_PATH").map(PathBuf::from),
+            data_source: get("DATA_SOURCE").map(|s| s.parse()).transpose()?,
+            watch_mode: get("WATCH_MODE").map(|s| s.parse()).transpose()?,
+            poll_interval_secs: get("POLL_INTERVAL_SECS")
+                .map(|s| parse_value("POLL_INTERVAL_SECS", &s))
+                .transpose()?,
+            logs_data: get("LOGS_DATA").map(PathBuf::from),
+            ui: UiConfig {
+                hide_read: get("HIDE_READ")
+                    .map(|s| parse_value("HIDE_READ", &s))
+                    .transpose()?,
+                auto_filter: get("AUTO_FILTER")
+                    .map(|s| parse_value("AUTO_FILTER", &s))
+                    .transpose()?,
+            },
+            playfab: PlayFabConfig {
+                timeout_secs: get("PLAYFAB_TIMEOUT_SECS")
+                    .map(|s| parse_value("PLAYFAB_TIMEOUT_SECS", &s))
+                    .transpose()?,
+                connect_timeout_secs: get("PLAYFAB_CONNECT_TIMEOUT_SECS")
+                    .map(|s| parse_value("PLAYFAB_CONNECT_TIMEOUT_SECS", &s))
+                    .transpose()?,
+                retries: get("PLAYFAB_RETRIES")
+                    .map(|s| parse_value("PLAYFAB_RETRIES", &s))
+                    .transpose()?,
+                base_url: get("PLAYFAB_BASE_URL"),
+            },
+        })
+    }
+
+    /// Reads command line options. `args` should include the program name.
+    pub fn from_args(args: &[String]) -> Result<Config> {
+        let value = |name: &str| arg_value(args, name);
+
+        Ok(Config {
+            data_path: value("--data-path").map(PathBuf::from),
+            data_source: if args.iter().any(|s| s == "--playfab") {
+                Some(DataSource::PlayFab)
+            } else {
+                value("--data-source").map(|s| s.parse()).transpose()?
+            },
+            watch_mode: value("--watch").map(|s| s.parse()).transpose()?,
+            poll_interval_secs: parse_arg(args, "--poll-interval-secs")?,
+            logs_data: value("--logs-data").map(PathBuf::from),
+            ui: UiConfig {
+                hide_read: parse_arg(args, "--hide-read")?,
+                auto_filter: parse_arg(args, "--auto-filter")?,
+            },
+            playfab: PlayFabConfig {
+                timeout_secs: parse_arg(args, "--playfab-timeout-secs")?,
+                connect_timeout_secs: parse_arg(args, "--playfab-connect-timeout-secs")?,
+                retries: parse_arg(args, "--playfab-retries")?,
+                base_url: value("--playfab-base-url").cloned(),
+            },
+        })
+    }
+
+    /// Layers `other` on top of this config
+    pub fn merge(self, other: Config) -> Config {
+        Config {
+            data_path: other.data_path.or(self.data_path),
+            data_source: other.data_source.or(self.data_source),
+            watch_mode: other.watch_mode.or(self.watch_mode),
+            poll_interval_secs: other.poll_interval_secs.or(self.poll_interval_secs),
+            logs_data: other.logs_data.or(self.logs_data),
+            ui: UiConfig {
+                hide_read: other.ui.hide_read.or(self.ui.hide_read),
+                auto_filter: other.ui.auto_filter.or(self.ui.auto_filter),
+            },
+            playfab: PlayFabConfig {
+                timeout_secs: other.playfab.timeout_secs.or(self.playfab.timeout_secs),
+                connect_timeout_secs: other
+                    .playfab
+                    .connect_timeout_secs
+                    .or(self.playfab.connect_timeout_secs),
+                retries: other.playfab.retries.or(self.playfab.retries),
+                base_url: other.playfab.base_url.or(self.playfab.base_url),
+            },
+        }
+    }
+
+    /// Fills in anything unset with defaults. `find_data_path` is only called if no data path
+    /// was configured.
+    pub fn into_options(self, find_data_path: impl FnOnce() -> Result<PathBuf>) -> Result<Options> {
+        let defaults = Options::default();
+        for (name, secs) in [
+            ("poll_interval_secs", self.poll_interval_secs),
+            ("playfab.timeout_secs", self.playfab.timeout_secs),
+            (
+                "playfab.connect_timeout_secs",
+                self.playfab.connect_timeout_secs,
+            ),
+        ] {
+            if secs == Some(0) {
+                return Err(anyhow!("{name} must be at least 1"));
+            }
+        }
+
+        Ok(Options {
+            gtfo_path: match self.data_path {
+                Some(path) => path,
+                None => find_data_path()?,
+            },
+            data_source: self.data_source.unwrap_or(defaults.data_source),
+            watch_mode: self.watch_mode.unwrap_or(defaults.watch_mode),
+            poll_interval: self
+                .poll_interval_secs
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.poll_interval),
+            hide_read: self.ui.hide_read,
+            auto_filter: self.ui.auto_filter,
+            playfab_timeout: self
+                .playfab
+                .timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.playfab_timeout),
+            playfab_connect_timeout: self
+                .playfab
+                .connect_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.playfab_connect_timeout),
+            playfab_retries: self.playfab.retries.unwrap_or(defaults.playfab_retries),
+            playfab_base_url: self.playfab.base_url,
+            logs_data: self.logs_data,
+        })
+    }
+}
+
+/// Resolves options from, in order of precedence, command line arguments, environment
+/// variables and the config file
+pub fn load_options(
+    args: &[String],
+    find_data_path: impl FnOnce() -> Result<PathBuf>,
+) -> Result<Options> {
+    let config_path = arg_value(args, "--config")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os(format!("{ENV_PREFIX}CONFIG")).map(PathBuf::from))
+        .or_else(Config::default_path);
+
+    let file_config = match config_path {
+        Some(path) if path.exists() => {
+            log::info!("Using config file '{}'", path.display());
+            Config::load(&path)?
+        }
+        Some(path) => {
+            log::debug!("No config file at '{}'", path.display());
+            Config::default()
+        }
+        None => Config::default(),
+    };
+
+    file_config
+        .merge(Config::from_env(|name| std::env::var(name).ok())?)
+        .merge(Config::from_args(args)?)
+        .into_options(find_data_path)
+}
+
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow!("Invalid value '{value}' for {ENV_PREFIX}{name}"))
+}
+
+/// The value after the argument called `name`
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a String> {
+    args.iter()
+        .position(|s| s == name)
+        .and_then(|i| args.get(i + 1))
+}
+
+fn parse_arg<T: FromStr>(args: &[String], name: &str) -> Result<Option<T>> {
+    arg_value(args, name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| anyhow!("Invalid value '{value}' for {name}"))
+        })
+        .transpose()
+}
+
+impl FromStr for DataSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "logs" => Ok(DataSource::Logs),
+            "playfab" => Ok(DataSource::PlayFab),
+            "hybrid" => Ok(DataSource::Hybrid),
+            _ => Err(anyhow!(
+                "Unknown data source '{s}'. Expected logs, playfab or hybrid"
+            )),
+        }
+    }
+}
+
+impl Display for DataSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataSource::Logs => write!(f, "logs"),
+            DataSource::PlayFab => write!(f, "playfab"),
+            DataSource::Hybrid => write!(f, "hybrid"),
+        }
+    }
+}
+
+impl FromStr for WatchMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "events" => Ok(WatchMode::Events),
+            "poll" => Ok(WatchMode::Poll),
+            "off" => Ok(WatchMode::Off),
+            _ => Err(anyhow!(
+                "Unknown watch mode '{s}'. Expected events, poll or off"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod config {
+        use crate::config::{Config, DataSource, PlayFabConfig, UiConfig, WatchMode};
+        use std::collections::HashMap;
+        use std::path::PathBuf;
+        use std::time::Duration;
+
+        fn args(s: &str) -> Vec<String> {
+            s.split_whitespace().map(String::from).collect()
+        }
+
+        #[test]
+        fn parses_toml() {
+            let config: Config = toml::from_str(
+                r#"
+                data_path = "/a/path"
+                data_source = "playfab"
+                watch_mode = "poll"
+
+                [ui]
+                hide_read = true
+
+                [playfab]
+                timeout_secs = 5
+                retries = 0
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(config.data_path, Some(PathBuf::from("/a/path")));
+            assert_eq!(config.data_source, Some(DataSource::PlayFab));
+            assert_eq!(config.watch_mode, Some(WatchMode::Poll));
+            assert_eq!(config.ui.hide_read, Some(true));
+            assert_eq!(config.ui.auto_filter, None);
+            assert_eq!(config.playfab.timeout_secs, Some(5));
+            assert_eq!(config.playfab.connect_timeout_secs, None);
+            assert_eq!(config.playfab.retries, Some(0));
+        }
+
+        #[test]
+        fn parses_data_sources() {
+            for (s, expected) in [
+                ("logs", DataSource::Logs),
+                ("PlayFab", DataSource::PlayFab),
+                ("hybrid", DataSource::Hybrid),
+            ] {
+                assert_eq!(s.parse::<DataSource>().unwrap(), expected);
+            }
+            assert!("both".parse::<DataSource>().is_err());
+        }
+
+        #[test]
+        fn rejects_unknown_keys() {
+            let result = toml::from_str::<Config>("hide_read = true");
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn reads_env_vars() {
+            let vars = HashMap::from([
+                ("GTFO_LOG_TRACKER_DATA_SOURCE", "playfab"),
+                ("GTFO_LOG_TRACKER_AUTO_FILTER", "false"),
+                ("GTFO_LOG_TRACKER_PLAYFAB_BASE_URL", "http://localhost:8080"),
+            ]);
+
+            let config = Config::from_env(|name| vars.get(name).map(|s| s.to_string())).unwrap();
+
+            assert_eq!(config.data_source, Some(DataSource::PlayFab));
+            assert_eq!(config.ui.auto_filter, Some(false));
+            assert_eq!(
+                config.playfab.base_url.as_deref(),
+                Some("http://localhost:8080")
+            );
+        }
+
+        #[test]
+        fn rejects_invalid_env_vars() {
+            let result = Config::from_env(|name| {
+                (name == "GTFO_LOG_TRACKER_HIDE_READ").then(|| "maybe".to_string())
+            });
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn reads_args() {
+            let config = Config::from_args(&args(
+                "tracker --data-path /a/path --playfab --logs-data logs.json",
+            ))
+            .unwrap();
+
+            assert_eq!(config.data_path, Some(PathBuf::from("/a/path")));
+            assert_eq!(config.data_source, Some(DataSource::PlayFab));
+            assert_eq!(config.logs_data, Some(PathBuf::from("logs.json")));
+        }
+
+        #[test]
+        fn reads_every_option_from_args() {
+            let config = Config::from_args(&args(
+                "tracker --poll-interval-secs 5 --hide-read true --auto-filter false \
+                 --playfab-timeout-secs 20 --playfab-connect-timeout-secs 3 \
+                 --playfab-retries 0 --playfab-base-url http://localhost:8080",
+            ))
+            .unwrap();
+
+            assert_eq!(config.poll_interval_secs, Some(5));
+            assert_eq!(config.ui.hide_read, Some(true));
+            assert_eq!(config.ui.auto_filter, Some(false));
+            assert_eq!(config.playfab.timeout_secs, Some(20));
+            assert_eq!(config.playfab.connect_timeout_secs, Some(3));
+            assert_eq!(config.playfab.retries, Some(0));
+            assert_eq!(
+                config.playfab.base_url.as_deref(),
+                Some("http://localhost:8080")
+            );
+        }
+
+        #[test]
+        fn rejects_invalid_args() {
+            let result = Config::from_args(&args("tracker --hide-read maybe"));
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn later_layers_take_precedence() {
+            let file = Config {
+                data_source: Some(DataSource::PlayFab),
+                ui: UiConfig {
+                    hide_read: Some(true),
+                    auto_filter: Some(false),
+                },
+                ..Config::default()
+            };
+            let args = Config {
+                data_source: Some(DataSource::Logs),
+                ..Config::default()
+            };
+
+            let config = file.merge(args);
+
+            assert_eq!(config.data_source, Some(DataSource::Logs));
+            assert_eq!(config.ui.hide_read, Some(true));
+        }
+
+        #[test]
+        fn fills_defaults() {
+            let options = Config::default()
+                .into_options(|| Ok(PathBuf::from("/found")))
+                .unwrap();
+
+            assert_eq!(options.gtfo_path, PathBuf::from("/found"));
+            assert_eq!(options.data_source, DataSource::Logs);
+            assert_eq!(options.watch_mode, WatchMode::Events);
+            assert_eq!(options.auto_filter, None);
+            assert_eq!(options.playfab_timeout, Duration::from_secs(30));
+            assert_eq!(options.playfab_retries, 2);
+        }
+
+        #[test]
+        fn rejects_zero_poll_interval() {
+            let config = Config {
+                poll_interval_secs: Some(0),
+                ..Config::default()
+            };
+
+            let result = config.into_options(|| Ok(PathBuf::from("/found")));
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn rejects_zero_play_fab_timeouts() {
+            for playfab in [
+                PlayFabConfig {
+                    timeout_secs: Some(0),
+                    ..PlayFabConfig::default()
+                },
+                PlayFabConfig {
+                    connect_timeout_secs: Some(0),
+                    ..PlayFabConfig::default()
+                },
+            ] {
+                let config = Config {
+                    playfab,
+                    ..Config::default()
+                };
+
+                let result = config.into_options(|| Ok(PathBuf::from("/found")));
+
+                assert!(result.is_err());
+            }
+        }
+
+        #[test]
+        fn only_finds_data_path_if_not_configured() {
+            let config = Config {
+                data_path: Some(PathBuf::from("/configured")),
+                ..Config::default()
+            };
+
+            let options = config
+                .into_options(|| panic!("shouldn't look for data path"))
+                .unwrap();
+
+            assert_eq!(options.gtfo_path, PathBuf::from("/configured"));
+        }
+    }
+}
@@ -0,0 +1,499 @@
+//! Builds the story log dataset from GTFO's datablocks, as dumped by tools such as
+//! GTFO-API's datablock dumper
+
+use crate::game_data::{Location, StoryLog};
+use crate::level_id::LevelId;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+pub const LEVEL_LAYOUT_FILE: &str = "LevelLayoutDataBlock.json";
+pub const DIMENSION_FILE: &str = "DimensionDataBlock.json";
+pub const RUNDOWN_FILE: &str = "RundownDataBlock.json";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DataBlockFile<T> {
+    blocks: Vec<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LevelLayoutBlock {
+    #[serde(rename = "name")]
+    name: String,
+    #[serde(rename = "persistentID")]
+    persistent_id: u32,
+    #[serde(default)]
+    zone_alias_start: i32,
+    #[serde(default)]
+    zones: Vec<Zone>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Zone {
+    local_index: Value,
+    #[serde(default = "no_alias_override")]
+    alias_override: i32,
+    #[serde(default)]
+    terminal_placements: Vec<TerminalPlacement>,
+    #[serde(default)]
+    specific_terminal_spawn_datas: Vec<TerminalPlacement>,
+}
+
+fn no_alias_override() -> i32 {
+    -1
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TerminalPlacement {
+    #[serde(default)]
+    local_log_files: Vec<LogFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LogFile {
+    file_name: String,
+    file_content: Value,
+    /// 0 if no sound plays when the log is opened
+    #[serde(default)]
+    attached_audio_file: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DimensionBlock {
+    #[serde(rename = "name")]
+    name: String,
+    #[serde(rename = "persistentID")]
+    persistent_id: u32,
+    dimension_data: DimensionData,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DimensionData {
+    #[serde(default)]
+    static_terminal_placements: Vec<TerminalPlacement>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RundownBlock {
+    #[serde(default)]
+    tier_a: Vec<Expedition>,
+    #[serde(default)]
+    tier_b: Vec<Expedition>,
+    #[serde(default)]
+    tier_c: Vec<Expedition>,
+    #[serde(default)]
+    tier_d: Vec<Expedition>,
+    #[serde(default)]
+    tier_e: Vec<Expedition>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Expedition {
+    level_layout_data: u32,
+    #[serde(default)]
+    dimension_datas: Vec<ExpeditionDimension>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ExpeditionDimension {
+    dimension_data: u32,
+}
+
+/// A log file placed in a level before logs with the same content are grouped together
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Placement {
+    id: u32,
+    level: LevelId,
+    zone: u16,
+    name: String,
+    audio_id: Option<u32>,
+}
+
+/// Reads the datablocks in `dir`. `RundownDataBlock.json` is optional but without it, logs in
+/// dimensions can't be placed in a level and are left out.
+pub fn generate_logs(dir: &Path) -> Result<Vec<StoryLog>> {
+    let read = |name: &str| {
+        let path = dir.join(name);
+        fs::read_to_string(&path).with_context(|| format!("Couldn't read '{}'", path.display()))
+    };
+    let rundowns = if dir.join(RUNDOWN_FILE).exists() {
+        Some(read(RUNDOWN_FILE)?)
+    } else {
+        log::warn!("No {RUNDOWN_FILE} in '{}'", dir.display());
+        None
+    };
+
+    logs_from_datablocks(
+        &read(LEVEL_LAYOUT_FILE)?,
+        &read(DIMENSION_FILE)?,
+        rundowns.as_deref(),
+    )
+}
+
+/// Builds the dataset from the contents of each datablock file. Logs are grouped by their
+/// content id and then by level, sorted by where they are first found.
+pub fn logs_from_datablocks(
+    level_layouts: &str,
+    dimensions: &str,
+    rundowns: Option<&str>,
+) -> Result<Vec<StoryLog>> {
+    let level_layouts: DataBlockFile<LevelLayoutBlock> = serde_json::from_str(level_layouts)
+        .with_context(|| format!("Invalid {LEVEL_LAYOUT_FILE}"))?;
+    let dimensions: DataBlockFile<DimensionBlock> =
+        serde_json::from_str(dimensions).with_context(|| format!("Invalid {DIMENSION_FILE}"))?;
+    let rundowns: Vec<RundownBlock> = match rundowns {
+        Some(json) => {
+            serde_json::from_str::<DataBlockFile<RundownBlock>>(json)
+                .with_context(|| format!("Invalid {RUNDOWN_FILE}"))?
+                .blocks
+        }
+        None => Vec::new(),
+    };
+
+    let levels: HashMap<u32, LevelId> = level_layouts
+        .blocks
+        .iter()
+        .filter_map(|block| Some((block.persistent_id, parse_level_name(&block.name)?)))
+        .collect();
+
+    let mut placements: BTreeSet<Placement> = level_layouts
+        .blocks
+        .iter()
+        .flat_map(level_layout_placements)
+        .collect();
+
+    let dimension_levels = dimension_levels(&rundowns, &levels);
+    // Both would mean the rundown datablock isn't laid out the way it's read here
+    if !rundowns.is_empty() && expeditions(&rundowns).next().is_none() {
+        log::warn!("No expeditions found in {RUNDOWN_FILE}");
+    } else if !rundowns.is_empty()
+        && !expeditions(&rundowns).any(|e| levels.contains_key(&e.level_layout_data))
+    {
+        log::warn!("No expedition in {RUNDOWN_FILE} uses a level layout in {LEVEL_LAYOUT_FILE}");
+    }
+    for dimension in &dimensions.blocks {
+        let files = dimension_log_files(dimension);
+        if files.is_empty() {
+            continue;
+        }
+        let Some(level) = dimension_levels.get(&dimension.persistent_id) else {
+            log::warn!(
+                "Dimension '{}' isn't used by any expedition. Skipping its {} logs.",
+                dimension.name,
+                files.len()
+            );
+            continue;
+        };
+        placements.extend(files.into_iter().map(|(id, name, audio_id)| Placement {
+            id,
+            level: *level,
+            zone: 0,
+            audio_id,
+            name,
+        }));
+    }
+
+    Ok(group_placements(placements))
+}
+
+/// Gets the level from a level layout name such as `R1_A1_Main` or `Rundown7_C2`. Extension
+/// levels are shortened to AX and CX.
+fn parse_level_name(name: &str) -> Option<LevelId> {
+    let mut parts = name.split('_');
+    let rundown = parts
+        .next()?
+        .replacen("Rundown", "", 1)
+        .replacen('R', "", 1)
+        .parse()
+        .ok()?;
+    let level =
+        parts
+            .next()?
+            .replacen("A2 BX Extension", "AX", 1)
+            .replacen("C4 DX Extension", "CX", 1);
+
+    LevelId::from_parts(rundown, &level).ok()
+}
+
+fn level_layout_placements(block: &LevelLayoutBlock) -> Vec<Placement> {
+    let Some(level) = parse_level_name(&block.name) else {
+        log::debug!("Skipping level layout '{}'", block.name);
+        return Vec::new();
+    };
+
+    block
+        .zones
+        .iter()
+        .flat_map(|zone| {
+            let Some(alias) = zone_alias(zone, block.zone_alias_start) else {
+                log::warn!(
+                    "Unknown zone {} in '{}'. Skipping its logs.",
+                    zone.local_index,
+                    block.name
+                );
+                return Vec::new();
+            };
+            zone.terminal_placements
+                .iter()
+                .chain(&zone.specific_terminal_spawn_datas)
+                .flat_map(|terminal| log_files(&terminal.local_log_files))
+                .map(|(id, name, audio_id)| Placement {
+                    id,
+                    level,
+                    zone: alias,
+                    name,
+                    audio_id,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The zone number shown in-game. Zones are numbered from the level's alias start unless the
+/// zone overrides it.
+fn zone_alias(zone: &Zone, zone_alias_start: i32) -> Option<u16> {
+    if zone.alias_override != -1 {
+        return u16::try_from(zone.alias_override).ok();
+    }
+
+    let index = match &zone.local_index {
+        Value::String(s) => s.strip_prefix("Zone_").unwrap_or(s).parse::<i32>().ok()?,
+        Value::Number(n) => i32::try_from(n.as_i64()?).ok()?,
+        _ => return None,
+    };
+
+    u16::try_from(index + zone_alias_start).ok()
+}
+
+fn dimension_log_files(dimension: &DimensionBlock) -> Vec<(u32, String, Option<u32>)> {
+    dimension
+        .dimension_data
+        .static_terminal_placements
+        .iter()
+        .flat_map(|terminal| log_files(&terminal.local_log_files))
+        .collect()
+}
+
+/// Maps each dimension to the level of the expedition that uses it
+fn dimension_levels(
+    rundowns: &[RundownBlock],
+    levels: &HashMap<u32, LevelId>,
+) -> HashMap<u32, LevelId> {
+    expeditions(rundowns)
+        .filter_map(|expedition| {
+            let level = levels.get(&expedition.level_layout_data)?;
+            Some(
+                expedition
+                    .dimension_datas
+                    .iter()
+                    .map(move |d| (d.dimension_data, *level)),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
+fn expeditions(rundowns: &[RundownBlock]) -> impl Iterator<Item = &Expedition> {
+    rundowns
+        .iter()
+        .flat_map(|r| [&r.tier_a, &r.tier_b, &r.tier_c, &r.tier_d, &r.tier_e])
+        .flatten()
+}
+
+/// Returns the content id, name and attached audio of each file that has content
+fn log_files(files: &[LogFile]) -> impl Iterator<Item = (u32, String, Option<u32>)> + '_ {
+    files.iter().filter_map(|file| {
+        let id = match &file.file_content {
+            Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+            Value::String(s) => s.parse().ok(),
+            _ => None,
+        };
+        match id {
+            Some(0) | None => None,
+            Some(id) => Some((
+                id,
+                file.file_name.clone(),
+                Some(file.attached_audio_file).filter(|audio| *audio != 0),
+            )),
+        }
+    })
+}
+
+fn group_placements(placements: BTreeSet<Placement>) -> Vec<StoryLog> {
+    let mut grouped: BTreeMap<u32, BTreeMap<LevelId, Location>> = BTreeMap::new();
+    for p in placements {
+        let location = grouped
+            .entry(p.id)
+            .or_default()
+            .entry(p.level)
+            .or_insert_with(|| Location {
+                level: p.level,
+                zones: Vec::new(),
+                name: p.name,
+                audio_id: None,
+            });
+        if !location.zones.contains(&p.zone) {
+            location.zones.push(p.zone);
+        }
+        location.audio_id = location.audio_id.or(p.audio_id);
+    }
+
+    let mut logs: Vec<StoryLog> = grouped
+        .into_iter()
+        .map(|(id, locations)| StoryLog {
+            id,
+            locations: locations
+                .into_values()
+                .map(|mut location| {
+                    location.zones.sort();
+                    location
+                })
+                .collect(),
+        })
+        .collect();
+    logs.sort_by(|a, b| {
+        let first = |log: &StoryLog| {
+            let l = &log.locations[0];
+            (l.level, l.zones.clone())
+        };
+        first(a).cmp(&first(b)).then(a.id.cmp(&b.id))
+    });
+
+    logs
+}
+
+#[cfg(test)]
+mod tests {
+    mod logs_from_datablocks {
+        use crate::datablocks::logs_from_datablocks;
+        use crate::game_data::StoryLog;
+        use crate::level_id::LevelId;
+
+        const LEVEL_LAYOUTS: &str =
+            include_str!("../tests/fixtures/datablocks/LevelLayoutDataBlock.json");
+        const DIMENSIONS: &str =
+            include_str!("../tests/fixtures/datablocks/DimensionDataBlock.json");
+        const RUNDOWNS: &str = include_str!("../tests/fixtures/datablocks/RundownDataBlock.json");
+
+        fn logs() -> Vec<StoryLog> {
+            logs_from_datablocks(LEVEL_LAYOUTS, DIMENSIONS, Some(RUNDOWNS)).unwrap()
+        }
+
+        fn find(logs: &[StoryLog], id: u32) -> &StoryLog {
+            logs.iter().find(|l| l.id == id).expect("log not found")
+        }
+
+        #[test]
+        fn numbers_zones_from_alias_start() {
+            let logs = logs();
+
+            let log = find(&logs, 1001);
+            assert_eq!(log.locations[0].level, LevelId::new(1, 'A', 1));
+            assert_eq!(log.locations[0].zones, vec![50]);
+            assert_eq!(log.locations[0].name, "AAA-BBB-CCC");
+        }
+
+        #[test]
+        fn keeps_attached_audio() {
+            let logs = logs();
+
+            assert_eq!(find(&logs, 1001).locations[0].audio_id, Some(52));
+            assert_eq!(find(&logs, 1002).locations[0].audio_id, None);
+        }
+
+        #[test]
+        fn uses_alias_override() {
+            let logs = logs();
+
+            assert_eq!(find(&logs, 1002).locations[0].zones, vec![99]);
+        }
+
+        #[test]
+        fn includes_specific_terminal_spawns() {
+            let logs = logs();
+
+            assert_eq!(find(&logs, 1003).locations[0].zones, vec![51]);
+        }
+
+        #[test]
+        fn groups_zones_in_same_level() {
+            let logs = logs();
+
+            let log = find(&logs, 1004);
+            assert_eq!(log.locations.len(), 1);
+            assert_eq!(log.locations[0].zones, vec![50, 51]);
+        }
+
+        #[test]
+        fn groups_levels_by_content_id() {
+            let logs = logs();
+
+            let log = find(&logs, 1005);
+            let levels: Vec<LevelId> = log.locations.iter().map(|l| l.level).collect();
+            assert_eq!(
+                levels,
+                vec![LevelId::new(1, 'A', 1), LevelId::new(7, 'C', 2)]
+            );
+        }
+
+        #[test]
+        fn shortens_extension_levels() {
+            let logs = logs();
+
+            let log = find(&logs, 1006);
+            assert_eq!(log.locations[0].level.to_string(), "R8AX");
+        }
+
+        #[test]
+        fn places_dimension_logs_in_expedition() {
+            let logs = logs();
+
+            let log = find(&logs, 2001);
+            assert_eq!(log.locations[0].level, LevelId::new(7, 'C', 2));
+            assert_eq!(log.locations[0].zones, vec![0]);
+        }
+
+        #[test]
+        fn skips_empty_files() {
+            let logs = logs();
+
+            assert!(logs.iter().all(|l| l.id != 0));
+            assert_eq!(logs.len(), 7);
+        }
+
+        #[test]
+        fn skips_dimensions_without_rundowns() {
+            let logs = logs_from_datablocks(LEVEL_LAYOUTS, DIMENSIONS, None).unwrap();
+
+            assert!(logs.iter().all(|l| l.id != 2001));
+        }
+
+        #[test]
+        fn sorts_by_first_location() {
+            let logs = logs();
+
+            let ids: Vec<u32> = logs.iter().map(|l| l.id).collect();
+            assert_eq!(ids, vec![1001, 1005, 1004, 1003, 1002, 2001, 1006]);
+        }
+
+        #[test]
+        fn fails_for_invalid_datablock() {
+            let result = logs_from_datablocks("{}", DIMENSIONS, None);
+
+            assert!(result.is_err());
+        }
+    }
+}
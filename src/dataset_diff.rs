@@ -0,0 +1,332 @@
+use crate::game_data::{Location, StoryLog};
+use crate::level_id::LevelId;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+/// A difference between two versions of the story log dataset
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added {
+        id: u32,
+        name: String,
+    },
+    Removed {
+        id: u32,
+        name: String,
+    },
+    /// A log with the same name in the same level now has a different id
+    IdChanged {
+        name: String,
+        level: LevelId,
+        old_id: u32,
+        new_id: u32,
+    },
+    Renamed {
+        id: u32,
+        level: LevelId,
+        old_name: String,
+        new_name: String,
+    },
+    /// Found in different zones of the same level
+    Moved {
+        id: u32,
+        name: String,
+        level: LevelId,
+        old_zones: Vec<u16>,
+        new_zones: Vec<u16>,
+    },
+    /// Now also found in another level
+    LevelAdded {
+        id: u32,
+        name: String,
+        level: LevelId,
+    },
+    /// No longer found in a level
+    LevelRemoved {
+        id: u32,
+        name: String,
+        level: LevelId,
+    },
+}
+
+impl Change {
+    /// Whether the change alters read/total counts for people with existing progress. Renames
+    /// and moves keep the id so saved progress still matches.
+    pub fn affects_progress(&self) -> bool {
+        match self {
+            Change::Added { .. }
+            | Change::Removed { .. }
+            | Change::IdChanged { .. }
+            | Change::LevelAdded { .. }
+            | Change::LevelRemoved { .. } => true,
+            Change::Renamed { .. } | Change::Moved { .. } => false,
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added { id, name } => write!(f, "added {id} {name}"),
+            Change::Removed { id, name } => write!(f, "removed {id} {name}"),
+            Change::IdChanged {
+                name,
+                level,
+                old_id,
+                new_id,
+            } => write!(f, "id changed {old_id} -> {new_id} {name} in {level}"),
+            Change::Renamed {
+                id,
+                level,
+                old_name,
+                new_name,
+            } => write!(f, "renamed {id} {old_name} -> {new_name} in {level}"),
+            Change::Moved {
+                id,
+                name,
+                level,
+                old_zones,
+                new_zones,
+            } => write!(
+                f,
+                "moved {id} {name} in {level} from zones {old_zones:?} to {new_zones:?}"
+            ),
+            Change::LevelAdded { id, name, level } => {
+                write!(f, "level added {id} {name} now also in {level}")
+            }
+            Change::LevelRemoved { id, name, level } => {
+                write!(f, "level removed {id} {name} no longer in {level}")
+            }
+        }
+    }
+}
+
+/// Compares datasets by id. Logs that were removed and added with the same name in the same
+/// level are reported as an id change rather than separately.
+pub fn diff(old: &[StoryLog], new: &[StoryLog]) -> Vec<Change> {
+    let old_by_id: BTreeMap<u32, &StoryLog> = old.iter().map(|l| (l.id, l)).collect();
+    let new_by_id: BTreeMap<u32, &StoryLog> = new.iter().map(|l| (l.id, l)).collect();
+
+    let mut removed: BTreeSet<u32> = old_by_id
+        .keys()
+        .filter(|id| !new_by_id.contains_key(id))
+        .copied()
+        .collect();
+    let mut added: BTreeSet<u32> = new_by_id
+        .keys()
+        .filter(|id| !old_by_id.contains_key(id))
+        .copied()
+        .collect();
+    let mut changes = Vec::new();
+
+    for old_id in removed.clone() {
+        let renumbered = old_by_id[&old_id].locations.iter().find_map(|old_loc| {
+            added
+                .iter()
+                .copied()
+                .find(|new_id| {
+                    new_by_id[new_id].locations.iter().any(|new_loc| {
+                        new_loc.level == old_loc.level && new_loc.name == old_loc.name
+                    })
+                })
+                .map(|new_id| (new_id, old_loc))
+        });
+        if let Some((new_id, location)) = renumbered {
+            removed.remove(&old_id);
+            added.remove(&new_id);
+            changes.push(Change::IdChanged {
+                name: location.name.clone(),
+                level: location.level,
+                old_id,
+                new_id,
+            });
+        }
+    }
+
+    changes.extend(removed.into_iter().map(|id| Change::Removed {
+        id,
+        name: log_name(old_by_id[&id]),
+    }));
+    changes.extend(added.into_iter().map(|id| Change::Added {
+        id,
+        name: log_name(new_by_id[&id]),
+    }));
+
+    for (id, old_log) in &old_by_id {
+        if let Some(new_log) = new_by_id.get(id) {
+            changes.extend(diff_locations(*id, old_log, new_log));
+        }
+    }
+
+    changes
+}
+
+fn diff_locations(id: u32, old: &StoryLog, new: &StoryLog) -> Vec<Change> {
+    let by_level = |log: &StoryLog| -> BTreeMap<LevelId, Location> {
+        log.locations.iter().map(|l| (l.level, l.clone())).collect()
+    };
+    let old = by_level(old);
+    let new = by_level(new);
+    let mut changes = Vec::new();
+
+    for (level, old_loc) in &old {
+        match new.get(level) {
+            Some(new_loc) => {
+                if old_loc.name != new_loc.name {
+                    changes.push(Change::Renamed {
+                        id,
+                        level: *level,
+                        old_name: old_loc.name.clone(),
+                        new_name: new_loc.name.clone(),
+                    });
+                }
+                if old_loc.zones != new_loc.zones {
+                    changes.push(Change::Moved {
+                        id,
+                        name: new_loc.name.clone(),
+                        level: *level,
+                        old_zones: old_loc.zones.clone(),
+                        new_zones: new_loc.zones.clone(),
+                    });
+                }
+            }
+            None => changes.push(Change::LevelRemoved {
+                id,
+                name: old_loc.name.clone(),
+                level: *level,
+            }),
+        }
+    }
+    for (level, new_loc) in &new {
+        if !old.contains_key(level) {
+            changes.push(Change::LevelAdded {
+                id,
+                name: new_loc.name.clone(),
+                level: *level,
+            });
+        }
+    }
+
+    changes
+}
+
+fn log_name(log: &StoryLog) -> String {
+    log.locations
+        .first()
+        .map(|l| format!("{} ({})", l.name, l.level))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    mod diff {
+        use crate::dataset_diff::{diff, Change};
+        use crate::game_data::StoryLog;
+        use crate::level_id::LevelId;
+        use crate::test_support::{location, story_log};
+
+        #[test]
+        fn no_changes() {
+            let logs = vec![story_log(1, "AAA", &["R1A1"])];
+
+            assert!(diff(&logs, &logs).is_empty());
+        }
+
+        #[test]
+        fn finds_added_and_removed() {
+            let old = vec![story_log(1, "AAA", &["R1A1"])];
+            let new = vec![story_log(2, "BBB", &["R1A2"])];
+
+            let changes = diff(&old, &new);
+
+            assert_eq!(
+                changes,
+                vec![
+                    Change::Removed {
+                        id: 1,
+                        name: "AAA (R1A1)".to_string()
+                    },
+                    Change::Added {
+                        id: 2,
+                        name: "BBB (R1A2)".to_string()
+                    }
+                ]
+            );
+            assert!(changes.iter().all(Change::affects_progress));
+        }
+
+        #[test]
+        fn matches_new_id_by_name() {
+            let old = vec![story_log(1, "AAA", &["R1A1"])];
+            let new = vec![story_log(2, "AAA", &["R1A1"])];
+
+            let changes = diff(&old, &new);
+
+            assert_eq!(
+                changes,
+                vec![Change::IdChanged {
+                    name: "AAA".to_string(),
+                    level: LevelId::new(1, 'A', 1),
+                    old_id: 1,
+                    new_id: 2
+                }]
+            );
+            assert!(changes[0].affects_progress());
+        }
+
+        #[test]
+        fn finds_renamed_and_moved() {
+            let old = vec![story_log(1, "AAA", &["R1A1"])];
+            let new = vec![StoryLog {
+                id: 1,
+                locations: vec![location("R1A1", vec![2], "BBB")],
+            }];
+
+            let changes = diff(&old, &new);
+
+            assert_eq!(
+                changes,
+                vec![
+                    Change::Renamed {
+                        id: 1,
+                        level: LevelId::new(1, 'A', 1),
+                        old_name: "AAA".to_string(),
+                        new_name: "BBB".to_string()
+                    },
+                    Change::Moved {
+                        id: 1,
+                        name: "BBB".to_string(),
+                        level: LevelId::new(1, 'A', 1),
+                        old_zones: vec![1],
+                        new_zones: vec![2]
+                    }
+                ]
+            );
+            assert!(!changes.iter().any(Change::affects_progress));
+        }
+
+        #[test]
+        fn finds_level_changes() {
+            let old = vec![story_log(1, "AAA", &["R1A1"])];
+            let new = vec![story_log(1, "AAA", &["R1B1"])];
+
+            let changes = diff(&old, &new);
+
+            assert_eq!(
+                changes,
+                vec![
+                    Change::LevelRemoved {
+                        id: 1,
+                        name: "AAA".to_string(),
+                        level: LevelId::new(1, 'A', 1)
+                    },
+                    Change::LevelAdded {
+                        id: 1,
+                        name: "AAA".to_string(),
+                        level: LevelId::new(1, 'B', 1)
+                    }
+                ]
+            );
+        }
+    }
+}
@@ -0,0 +1,349 @@
+use crate::game_data::{map_log_to_rows, LogRow, StoryLog};
+use crate::level_id::LevelId;
+use anyhow::{anyhow, Context, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter, Write};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+    BbCode,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Json,
+        ExportFormat::Csv,
+        ExportFormat::Markdown,
+        ExportFormat::BbCode,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+            ExportFormat::BbCode => "txt",
+        }
+    }
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::BbCode => "BBCode",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "bbcode" => Ok(ExportFormat::BbCode),
+            _ => Err(anyhow!(
+                "Unknown export format '{s}'. Expected json, csv, markdown or bbcode"
+            )),
+        }
+    }
+}
+
+pub fn export(
+    all_logs: &[StoryLog],
+    read_log_ids: &HashSet<u32>,
+    format: ExportFormat,
+) -> Result<String> {
+    match format {
+        ExportFormat::Json => {
+            let rows: Vec<LogRow> = all_logs
+                .iter()
+                .flat_map(|log| map_log_to_rows(log, read_log_ids))
+                .collect();
+            Ok(serde_json::to_string_pretty(&rows)?)
+        }
+        ExportFormat::Csv => Ok(to_csv(all_logs, read_log_ids)),
+        ExportFormat::Markdown => Ok(to_checklist(all_logs, read_log_ids, &MARKDOWN_CHECKLIST)),
+        ExportFormat::BbCode => Ok(to_checklist(all_logs, read_log_ids, &BBCODE_CHECKLIST)),
+    }
+}
+
+pub fn export_to_file(
+    all_logs: &[StoryLog],
+    read_log_ids: &HashSet<u32>,
+    format: ExportFormat,
+    path: &Path,
+) -> Result<()> {
+    let output = export(all_logs, read_log_ids, format)?;
+    fs::write(path, output).with_context(|| format!("Couldn't write '{}'", path.display()))
+}
+
+/// Where the GUI writes exports since it has no save dialog. The file name includes `time` so
+/// earlier exports aren't overwritten.
+pub fn default_export_path(format: ExportFormat, time: OffsetDateTime) -> Result<PathBuf> {
+    dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .map(|dir| export_path_in(&dir, format, time))
+        .ok_or_else(|| anyhow!("Unable to get documents directory"))
+}
+
+/// A file in `dir` named after `time` that doesn't exist yet
+fn export_path_in(dir: &Path, format: ExportFormat, time: OffsetDateTime) -> PathBuf {
+    let name = format!(
+        "gtfo-log-tracker-progress-{:04}-{:02}-{:02}-{:02}{:02}{:02}",
+        time.year(),
+        u8::from(time.month()),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    );
+
+    (1..)
+        .map(|n| match n {
+            1 => dir.join(format!("{name}.{}", format.extension())),
+            _ => dir.join(format!("{name}-{n}.{}", format.extension())),
+        })
+        .find(|path| !path.exists())
+        .expect("there's always an unused file name")
+}
+
+fn to_csv(all_logs: &[StoryLog], read_log_ids: &HashSet<u32>) -> String {
+    all_logs
+        .iter()
+        .flat_map(|log| map_log_to_rows(log, read_log_ids))
+        .fold(
+            "read,level,zone,name,id,audio\n".to_string(),
+            |mut output, row| {
+                let _ = writeln!(
+                    output,
+                    "{},{},{},{},{},{}",
+                    row.read,
+                    csv_field(&row.level.to_string()),
+                    csv_field(&row.zone),
+                    csv_field(&row.name),
+                    row.id,
+                    row.audio_id.map(|id| id.to_string()).unwrap_or_default()
+                );
+                output
+            },
+        )
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Markup used to write a checklist grouped by rundown and level
+struct Checklist {
+    rundown: fn(&mut String, u8),
+    level_start: fn(&mut String, &str),
+    level_end: &'static str,
+    item: fn(&mut String, &LogRow),
+}
+
+const MARKDOWN_CHECKLIST: Checklist = Checklist {
+    rundown: |output, rundown| {
+        let _ = writeln!(output, "## Rundown {rundown}\n");
+    },
+    level_start: |output, level| {
+        let _ = writeln!(output, "### {level}\n");
+    },
+    level_end: "\n",
+    item: |output, row| {
+        let check = if row.read { "x" } else { " " };
+        let _ = writeln!(
+            output,
+            "- [{check}] {} (zone {}) `{}`",
+            row.name, row.zone, row.id
+        );
+    },
+};
+
+const BBCODE_CHECKLIST: Checklist = Checklist {
+    rundown: |output, rundown| {
+        let _ = writeln!(output, "[size=150][b]Rundown {rundown}[/b][/size]");
+    },
+    level_start: |output, level| {
+        let _ = writeln!(output, "[b]{level}[/b]\n[list]");
+    },
+    level_end: "[/list]\n",
+    item: |output, row| {
+        let row_text = format!("{} (zone {}) {}", row.name, row.zone, row.id);
+        let _ = if row.read {
+            writeln!(output, "[*][s]{row_text}[/s]")
+        } else {
+            writeln!(output, "[*]{row_text}")
+        };
+    },
+};
+
+fn to_checklist(
+    all_logs: &[StoryLog],
+    read_log_ids: &HashSet<u32>,
+    checklist: &Checklist,
+) -> String {
+    let mut rundowns: BTreeMap<u8, BTreeMap<LevelId, Vec<LogRow>>> = BTreeMap::new();
+    for row in all_logs
+        .iter()
+        .flat_map(|log| map_log_to_rows(log, read_log_ids))
+    {
+        rundowns
+            .entry(row.level.rundown)
+            .or_default()
+            .entry(row.level)
+            .or_default()
+            .push(row);
+    }
+
+    let mut output = String::new();
+    for (rundown, levels) in rundowns {
+        (checklist.rundown)(&mut output, rundown);
+        for (level, rows) in levels {
+            (checklist.level_start)(&mut output, &level.to_string());
+            for row in rows {
+                (checklist.item)(&mut output, &row);
+            }
+            output.push_str(checklist.level_end);
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    mod export {
+        use crate::export::{export, ExportFormat};
+        use crate::game_data::{Location, StoryLog};
+        use crate::test_support::location;
+        use std::collections::HashSet;
+
+        fn logs() -> Vec<StoryLog> {
+            vec![
+                StoryLog {
+                    id: 1,
+                    locations: vec![location("R1A1", vec![40, 42], "AAA-BBB-CCC")],
+                },
+                StoryLog {
+                    id: 2,
+                    locations: vec![Location {
+                        audio_id: Some(52),
+                        ..location("R2B1", vec![0], "DDD-EEE-FFF")
+                    }],
+                },
+            ]
+        }
+
+        #[test]
+        fn json_has_a_row_per_location() {
+            let output = export(&logs(), &HashSet::from([1]), ExportFormat::Json).unwrap();
+            let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+            assert_eq!(
+                json,
+                serde_json::json!([
+                    {"level": "R1A1", "zone": "40, 42", "name": "AAA-BBB-CCC", "id": 1, "read": true},
+                    {"level": "R2B1", "zone": "Outside", "name": "DDD-EEE-FFF", "id": 2, "read": false, "audioId": 52}
+                ])
+            );
+        }
+
+        #[test]
+        fn csv_quotes_fields_with_commas() {
+            let output = export(&logs(), &HashSet::from([1]), ExportFormat::Csv).unwrap();
+
+            assert_eq!(
+                output,
+                "read,level,zone,name,id,audio\n\
+                 true,R1A1,\"40, 42\",AAA-BBB-CCC,1,\n\
+                 false,R2B1,Outside,DDD-EEE-FFF,2,52\n"
+            );
+        }
+
+        #[test]
+        fn markdown_groups_by_rundown_and_level() {
+            let output = export(&logs(), &HashSet::from([1]), ExportFormat::Markdown).unwrap();
+
+            assert_eq!(
+                output,
+                "## Rundown 1\n\n### R1A1\n\n- [x] AAA-BBB-CCC (zone 40, 42) `1`\n\n\
+                 ## Rundown 2\n\n### R2B1\n\n- [ ] DDD-EEE-FFF (zone Outside) `2`\n\n"
+            );
+        }
+
+        #[test]
+        fn bbcode_strikes_read_logs() {
+            let output = export(&logs(), &HashSet::from([1]), ExportFormat::BbCode).unwrap();
+
+            assert!(output.contains("[*][s]AAA-BBB-CCC (zone 40, 42) 1[/s]\n"));
+            assert!(output.contains("[*]DDD-EEE-FFF (zone Outside) 2\n"));
+        }
+    }
+    mod export_path_in {
+        use crate::export::{export_path_in, ExportFormat};
+        use crate::test_support::test_dir;
+        use std::fs;
+        use time::macros::datetime;
+
+        #[test]
+        fn names_file_after_time() {
+            let dir = test_dir("export-path");
+
+            let path = export_path_in(&dir, ExportFormat::Csv, datetime!(2024-02-14 09:05:03 +1));
+
+            assert_eq!(
+                path,
+                dir.join("gtfo-log-tracker-progress-2024-02-14-090503.csv")
+            );
+        }
+
+        #[test]
+        fn does_not_overwrite_existing_export() {
+            let dir = test_dir("export-path-existing");
+            let time = datetime!(2024-02-14 09:05:03 UTC);
+            fs::write(export_path_in(&dir, ExportFormat::Json, time), "[]").unwrap();
+
+            let path = export_path_in(&dir, ExportFormat::Json, time);
+
+            assert_eq!(
+                path,
+                dir.join("gtfo-log-tracker-progress-2024-02-14-090503-2.json")
+            );
+        }
+    }
+    mod export_format {
+        use crate::export::ExportFormat;
+
+        #[test]
+        fn parses_case_insensitive() {
+            let format = "Markdown".parse::<ExportFormat>().unwrap();
+
+            assert_eq!(format, ExportFormat::Markdown);
+        }
+
+        #[test]
+        fn rejects_unknown_format() {
+            let result = "xml".parse::<ExportFormat>();
+
+            assert!(result.is_err());
+        }
+    }
+}
@@ -1,75 +1,329 @@
-use anyhow::{Context, Result};
-use serde::Deserialize;
-use std::path::PathBuf;
+use crate::level_id::LevelId;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter, Write};
+use std::fs;
+use std::path::{Path, PathBuf};
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct StoryLog {
     pub id: u32,
     pub locations: Vec<Location>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "LocationFile", into = "LocationFile")]
 pub struct Location {
-    pub rundown: u8,
-    pub level: String,
+    pub level: LevelId,
     pub zones: Vec<u16>,
     pub name: String,
+    /// Sound that plays when the log is opened in this level
+    pub audio_id: Option<u32>,
 }
 
-pub fn get_id_from_name(name: &str, logs: &[StoryLog]) -> Option<u32> {
-    logs.iter().find_map(|log| {
-        if log.locations.iter().any(|loc| loc.name == name) {
-            Some(log.id)
-        } else {
-            None
+impl Location {
+    pub fn has_audio(&self) -> bool {
+        self.audio_id.is_some()
+    }
+}
+
+/// How a location is stored in logs.json, with the rundown and level kept separate
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LocationFile {
+    rundown: u8,
+    level: String,
+    zones: Vec<u16>,
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    audio_id: Option<u32>,
+}
+
+impl TryFrom<LocationFile> for Location {
+    type Error = anyhow::Error;
+
+    fn try_from(file: LocationFile) -> Result<Self> {
+        Ok(Location {
+            level: LevelId::from_parts(file.rundown, &file.level)?,
+            zones: file.zones,
+            name: file.name,
+            audio_id: file.audio_id.filter(|id| *id != 0),
+        })
+    }
+}
+
+impl From<Location> for LocationFile {
+    fn from(location: Location) -> Self {
+        LocationFile {
+            rundown: location.level.rundown,
+            level: location.level.level_name(),
+            zones: location.zones,
+            name: location.name,
+            audio_id: location.audio_id,
         }
+    }
+}
+
+/// A single location of a log, as shown in the log table
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRow {
+    pub level: LevelId,
+    pub zone: String,
+    pub name: String,
+    pub id: u32,
+    pub read: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_id: Option<u32>,
+}
+
+impl LogRow {
+    /// Case-insensitive match against any of the row's columns. A filter that is a level,
+    /// such as R1B1, only matches rows in exactly that level.
+    pub fn matches(&self, filter: &str) -> bool {
+        if let Ok(level) = filter.trim().parse::<LevelId>() {
+            return self.level == level;
+        }
+
+        let f = filter.to_ascii_lowercase();
+        self.level.to_string().to_ascii_lowercase().contains(&f)
+            || self.name.to_ascii_lowercase().contains(&f)
+            || self.zone.to_ascii_lowercase().contains(&f)
+            || self.id.to_string().contains(&f)
+    }
+}
+
+pub fn map_log_to_rows<'a>(
+    log: &'a StoryLog,
+    read_log_ids: &'a HashSet<u32>,
+) -> impl Iterator<Item = LogRow> + 'a {
+    log.locations.iter().map(|loc| LogRow {
+        level: loc.level,
+        name: loc.name.to_string(),
+        id: log.id,
+        read: read_log_ids.contains(&log.id),
+        audio_id: loc.audio_id,
+        zone: if loc.zones == vec![0] {
+            "Outside".to_string()
+        } else {
+            comma_join(&loc.zones)
+        },
     })
 }
 
-// Parses strings like Local_32,2,0 to R1B1
-pub fn get_level_from_local(id: &str) -> Option<String> {
-    let parts: Vec<&str> = id.split(',').collect();
-    if parts.len() != 3 {
-        return None;
+fn comma_join(nums: &[u16]) -> String {
+    nums.iter()
+        .enumerate()
+        .fold(String::new(), |mut output, (i, num)| {
+            if i == 0 {
+                let _ = write!(output, "{num}");
+            } else {
+                let _ = write!(output, ", {num}");
+            }
+            output
+        })
+}
+
+#[derive(Debug, PartialEq)]
+pub enum NameLookup {
+    Unique(u32),
+    /// More than one log uses the name and the level didn't narrow it down to one
+    Ambiguous(Vec<u32>),
+    Unknown,
+}
+
+/// Finds the log with the given name. Some names are shared between logs in different levels,
+/// in which case `level` (e.g. R7D1) is used to pick the right one.
+pub fn get_id_from_name(name: &str, level: Option<LevelId>, logs: &[StoryLog]) -> NameLookup {
+    let candidates: Vec<(u32, &Location)> = logs
+        .iter()
+        .flat_map(|log| {
+            log.locations
+                .iter()
+                .filter(|loc| loc.name == name)
+                .map(move |loc| (log.id, loc))
+        })
+        .collect();
+
+    let mut ids: Vec<u32> = candidates.iter().map(|(id, _)| *id).collect();
+    ids.dedup();
+
+    match ids.as_slice() {
+        [] => return NameLookup::Unknown,
+        [id] => return NameLookup::Unique(*id),
+        _ => {}
     }
 
-    let rundown = match parts[0] {
-        "Local_32" => 1,
-        "Local_33" => 2,
-        "Local_34" => 3,
-        "Local_37" => 4,
-        "Local_38" => 5,
-        "Local_41" => 6,
-        "Local_31" => 7,
-        "Local_35" => 8,
-        _ => return None,
-    };
-    let tier = match parts[1] {
-        "1" => "A",
-        "2" => "B",
-        "3" => "C",
-        "4" => "D",
-        "5" => "E",
-        _ => return None,
+    let mut in_level: Vec<u32> = candidates
+        .iter()
+        .filter(|(_, loc)| Some(loc.level) == level)
+        .map(|(id, _)| *id)
+        .collect();
+    in_level.dedup();
+
+    match in_level.as_slice() {
+        [id] => NameLookup::Unique(*id),
+        _ => NameLookup::Ambiguous(ids),
+    }
+}
+
+/// How the game's expedition ids map to rundowns and tiers. The game names rundowns with
+/// `Local_` ids that have no relation to the rundown number.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LevelMap {
+    pub rundowns: BTreeMap<String, u8>,
+    pub tiers: BTreeMap<u8, char>,
+}
+
+impl LevelMap {
+    /// The mapping compiled into the tracker
+    pub fn built_in() -> LevelMap {
+        serde_json::from_str(include_str!("../data/levels.json"))
+            .expect("Built-in levels.json must be valid")
+    }
+
+    /// Parses strings like Local_32,2,0 to R1B1. Returns `None` for rundowns or tiers that
+    /// aren't in the map.
+    pub fn get_level_from_local(&self, id: &str) -> Option<LevelId> {
+        let parts: Vec<&str> = id.split(',').collect();
+        if parts.len() != 3 {
+            return None;
+        }
+
+        let rundown = self.rundowns.get(parts[0])?;
+        let tier = self.tiers.get(&parts[1].parse::<u8>().ok()?)?;
+        let expedition = parts[2].parse::<u8>().ok()? + 1;
+
+        Some(LevelId::new(*rundown, *tier, expedition))
+    }
+}
+
+/// Version of the dataset compiled into the tracker, the GTFO patch it was generated from
+const EMBEDDED_DATASET_VERSION: &str = "2024-02-14";
+
+/// The story logs in use along with where they came from
+#[derive(Debug, Clone)]
+pub struct Dataset {
+    pub logs: Vec<StoryLog>,
+    pub levels: LevelMap,
+    pub version: Option<String>,
+    /// File the dataset was loaded from. `None` for the built-in copy.
+    pub path: Option<PathBuf>,
+}
+
+impl Display for Dataset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let version = self.version.as_deref().unwrap_or("unversioned");
+        match &self.path {
+            Some(path) => write!(f, "{version} ({})", path.display()),
+            None => write!(f, "{version} (built-in)"),
+        }
+    }
+}
+
+/// A dataset file is either a plain array of logs or an object with a version. Datasets
+/// without a level map use the built-in one.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VersionedDataset {
+    version: String,
+    logs: Vec<StoryLog>,
+    levels: Option<LevelMap>,
+}
+
+/// Loads the dataset at `path`, or the built-in one if there isn't one
+pub fn load_dataset(path: Option<&Path>) -> Result<Dataset> {
+    match path {
+        Some(path) => {
+            let json = fs::read_to_string(path)
+                .with_context(|| format!("Couldn't read logs data '{}'", path.display()))?;
+            let dataset = parse_dataset(&json)
+                .with_context(|| format!("Invalid logs data '{}'", path.display()))?;
+            log::info!(
+                "Loaded {} logs from '{}'",
+                dataset.logs.len(),
+                path.display()
+            );
+
+            Ok(Dataset {
+                path: Some(path.to_path_buf()),
+                ..dataset
+            })
+        }
+        None => {
+            let dataset = parse_dataset(include_str!("../data/logs.json"))
+                .with_context(|| "Failed to parse built-in logs.json")?;
+
+            Ok(Dataset {
+                version: Some(EMBEDDED_DATASET_VERSION.to_string()),
+                ..dataset
+            })
+        }
+    }
+}
+
+fn parse_dataset(json: &str) -> Result<Dataset> {
+    let dataset = if json.trim_start().starts_with('{') {
+        let dataset: VersionedDataset = serde_json::from_str(json)?;
+        Dataset {
+            logs: dataset.logs,
+            levels: dataset.levels.unwrap_or_else(LevelMap::built_in),
+            version: Some(dataset.version),
+            path: None,
+        }
+    } else {
+        Dataset {
+            logs: serde_json::from_str(json)?,
+            levels: LevelMap::built_in(),
+            version: None,
+            path: None,
+        }
     };
-    let expedition = parts[2].parse::<u8>().ok()? + 1;
+    validate_logs(&dataset.logs)?;
 
-    Some(format!("R{rundown}{tier}{expedition}"))
+    Ok(dataset)
 }
 
-pub fn load_logs() -> Result<Vec<StoryLog>> {
-    let logs = serde_json::from_str::<Vec<StoryLog>>(include_str!("../data/logs.json"))
-        .with_context(|| "Failed to parse logs.json")?;
+/// Checks for mistakes serde can't catch, such as duplicate ids or logs without a location
+fn validate_logs(logs: &[StoryLog]) -> Result<()> {
+    if logs.is_empty() {
+        return Err(anyhow!("Dataset doesn't contain any logs"));
+    }
 
-    Ok(logs)
+    let mut ids = HashSet::new();
+    for log in logs {
+        if !ids.insert(log.id) {
+            return Err(anyhow!("Log {} appears more than once", log.id));
+        }
+        if log.locations.is_empty() {
+            return Err(anyhow!("Log {} has no locations", log.id));
+        }
+        for location in &log.locations {
+            if location.name.is_empty() {
+                return Err(anyhow!("Log {} has a location without a name", log.id));
+            }
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(target_os = "linux")]
 pub fn find_user_data_path() -> Result<PathBuf> {
-    crate::steam::find_proton_app_data_path()
+    let candidates: Vec<PathBuf> = crate::steam::find_proton_app_data_paths()
+        .into_iter()
         .map(|p| p.join("LocalLow/10 Chambers Collective/GTFO"))
+        .collect();
+    log::debug!("GTFO user data path candidates: {candidates:?}");
+
+    candidates
+        .iter()
+        .find(|p| p.exists())
+        .or_else(|| candidates.first())
+        .cloned()
         .ok_or_else(|| anyhow::anyhow!("Couldn't find compatdata AppData path"))
 }
 
@@ -87,3 +341,198 @@ pub fn find_user_data_path() -> Result<PathBuf> {
         .map(|app_data| app_data.join(r"10 Chambers Collective\GTFO"))
         .with_context(|| "Couldn't find AppData path")
 }
+
+#[cfg(test)]
+mod tests {
+    mod get_id_from_name {
+        use crate::game_data::{get_id_from_name, NameLookup, StoryLog};
+        use crate::test_support::story_log;
+
+        fn logs() -> Vec<StoryLog> {
+            vec![
+                story_log(1, "2MD-N3H-SYH", &["R7D1"]),
+                story_log(2, "2MD-N3H-SYH", &["R8C1"]),
+                story_log(3, "AAA-BBB-CCC", &["R1A1"]),
+            ]
+        }
+
+        #[test]
+        fn finds_unique_name() {
+            let result = get_id_from_name("AAA-BBB-CCC", None, &logs());
+
+            assert_eq!(result, NameLookup::Unique(3));
+        }
+
+        #[test]
+        fn uses_level_for_shared_name() {
+            let result = get_id_from_name("2MD-N3H-SYH", "R8C1".parse().ok(), &logs());
+
+            assert_eq!(result, NameLookup::Unique(2));
+        }
+
+        #[test]
+        fn shared_name_without_level_is_ambiguous() {
+            let result = get_id_from_name("2MD-N3H-SYH", None, &logs());
+
+            assert_eq!(result, NameLookup::Ambiguous(vec![1, 2]));
+        }
+
+        #[test]
+        fn shared_name_in_other_level_is_ambiguous() {
+            let result = get_id_from_name("2MD-N3H-SYH", "R1A1".parse().ok(), &logs());
+
+            assert_eq!(result, NameLookup::Ambiguous(vec![1, 2]));
+        }
+
+        #[test]
+        fn unknown_name() {
+            let result = get_id_from_name("ZZZ-ZZZ-ZZZ", None, &logs());
+
+            assert_eq!(result, NameLookup::Unknown);
+        }
+    }
+
+    mod load_dataset {
+        use crate::game_data::{load_dataset, parse_dataset, LevelMap};
+        use crate::level_id::LevelId;
+        use crate::test_support::test_dir;
+        use std::fs;
+        use std::path::Path;
+
+        const LOG: &str = r#"{"id": 1, "locations": [{"rundown": 1, "level": "A1", "zones": [1], "name": "AAA-BBB-CCC"}]}"#;
+
+        #[test]
+        fn loads_built_in_dataset() {
+            let dataset = load_dataset(None).unwrap();
+
+            assert!(!dataset.logs.is_empty());
+            assert!(dataset.to_string().ends_with("(built-in)"));
+        }
+
+        #[test]
+        #[ignore = "data/logs.json needs regenerating from a datablock dump with generate-logs"]
+        fn built_in_dataset_has_audio_ids() {
+            let dataset = load_dataset(None).unwrap();
+
+            assert!(dataset
+                .logs
+                .iter()
+                .flat_map(|log| &log.locations)
+                .any(|location| location.audio_id.is_some()));
+        }
+
+        #[test]
+        fn loads_dataset_file() {
+            let dir = test_dir("logs-data");
+            let path = dir.join("logs.json");
+            fs::write(
+                &path,
+                format!(r#"{{"version": "2024-03-01", "logs": [{LOG}]}}"#),
+            )
+            .unwrap();
+
+            let dataset = load_dataset(Some(&path)).unwrap();
+
+            assert_eq!(dataset.logs.len(), 1);
+            assert_eq!(dataset.version.as_deref(), Some("2024-03-01"));
+            assert_eq!(dataset.path.as_deref(), Some(path.as_path()));
+        }
+
+        #[test]
+        fn accepts_plain_array() {
+            let dataset = parse_dataset(&format!("[{LOG}]")).unwrap();
+
+            assert_eq!(dataset.version, None);
+            assert_eq!(dataset.logs.len(), 1);
+            assert_eq!(dataset.levels, LevelMap::built_in());
+        }
+
+        #[test]
+        fn reads_level_map() {
+            let dataset = parse_dataset(&format!(
+                r#"{{"version": "1", "logs": [{LOG}], "levels": {{"rundowns": {{"Local_99": 9}}, "tiers": {{"1": "A"}}}}}}"#
+            ))
+            .unwrap();
+
+            assert_eq!(
+                dataset.levels.get_level_from_local("Local_99,1,0"),
+                Some(LevelId::new(9, 'A', 1))
+            );
+        }
+
+        #[test]
+        fn reads_audio_id() {
+            let dataset = parse_dataset(
+                r#"[{"id": 1, "locations": [{"rundown": 1, "level": "A1", "zones": [1], "name": "AAA", "audioId": 52}]}]"#,
+            )
+            .unwrap();
+
+            let location = &dataset.logs[0].locations[0];
+            assert_eq!(location.audio_id, Some(52));
+            assert!(location.has_audio());
+        }
+
+        #[test]
+        fn treats_missing_or_zero_audio_as_none() {
+            let dataset = parse_dataset(&format!(
+                r#"[{LOG}, {{"id": 2, "locations": [{{"rundown": 1, "level": "A1", "zones": [1], "name": "BBB", "audioId": 0}}]}}]"#
+            ))
+            .unwrap();
+
+            assert!(dataset.logs.iter().all(|log| !log.locations[0].has_audio()));
+        }
+
+        #[test]
+        fn reports_missing_fields() {
+            let error = parse_dataset(r#"[{"id": 1, "locations": [{"rundown": 1}]}]"#).unwrap_err();
+
+            assert!(error.to_string().contains("missing field"));
+        }
+
+        #[test]
+        fn rejects_duplicate_ids() {
+            let result = parse_dataset(&format!("[{LOG}, {LOG}]"));
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn rejects_empty_dataset() {
+            let result = parse_dataset("[]");
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn fails_for_missing_file() {
+            let result = load_dataset(Some(Path::new("/does/not/exist.json")));
+
+            assert!(result.is_err());
+        }
+    }
+    mod get_level_from_local {
+        use crate::game_data::LevelMap;
+        use crate::level_id::LevelId;
+
+        #[test]
+        fn maps_known_rundown() {
+            let level = LevelMap::built_in().get_level_from_local("Local_35,3,0");
+
+            assert_eq!(level, Some(LevelId::new(8, 'C', 1)));
+        }
+
+        #[test]
+        fn unknown_rundown() {
+            let level = LevelMap::built_in().get_level_from_local("Local_99,1,0");
+
+            assert_eq!(level, None);
+        }
+
+        #[test]
+        fn unknown_tier() {
+            let level = LevelMap::built_in().get_level_from_local("Local_32,6,0");
+
+            assert_eq!(level, None);
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use crate::game_data::{self, LevelMap, NameLookup, StoryLog};
+use crate::level_id::LevelId;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashSet;
+use time::Time;
+
+static TIMESTAMP_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\d{2}):(\d{2}):(\d{2})\.(\d{3})").unwrap());
+
+static INGAME_READ_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"([A-Z0-9]{3,4}-[A-Z0-9]{3,6}(?:-[A-Z0-9]{3})?)").unwrap());
+
+static LEVEL_CHANGE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"SelectActiveExpedition.*(Local_\d+,\d,\d)").unwrap());
+
+static PREVIOUSLY_READ_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"Logs Read: \d+ / \d+ \| IDs: \[(\d+(?:,\s*\d+)*)]\s*$").unwrap());
+
+static GAME_STATE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)GameStateManager\W*Change\s*State.*\bto\s*:?\s*(\w+)").unwrap());
+
+/// Something that happened in-game, parsed from a line of GTFO's session log
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    /// An expedition was selected in the lobby, such as R1B1
+    ExpeditionSelected(LevelId),
+    /// An expedition the level map doesn't know was selected, such as after a content update.
+    /// Holds the game's id for it, such as Local_32,2,0.
+    UnknownExpedition(String),
+    /// The team dropped into the level
+    ExpeditionStarted,
+    LogRead(u32),
+    /// Every log read by the team so far, written when the game starts
+    ReadSummary(HashSet<u32>),
+    ExpeditionSucceeded,
+    ExpeditionFailed,
+    ReturnedToLobby,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedEvent {
+    /// Time of day from the start of the line. Player.log lines don't have one.
+    pub time: Option<Time>,
+    pub event: GameEvent,
+}
+
+/// Turns log lines into [`GameEvent`]s. Keeps track of the selected expedition so logs that
+/// share a name can be told apart.
+pub struct EventParser<'a> {
+    logs: &'a [StoryLog],
+    levels: &'a LevelMap,
+    level: Option<LevelId>,
+}
+
+impl<'a> EventParser<'a> {
+    pub fn new(logs: &'a [StoryLog], levels: &'a LevelMap) -> Self {
+        Self {
+            logs,
+            levels,
+            level: None,
+        }
+    }
+
+    /// The most recently selected expedition
+    pub fn level(&self) -> Option<LevelId> {
+        self.level
+    }
+
+    pub fn parse_line(&mut self, line: &str) -> Vec<TimedEvent> {
+        let time = parse_time(line);
+        let mut events = Vec::new();
+
+        if let Some(ids) = PREVIOUSLY_READ_REGEX
+            .captures(line)
+            .and_then(|c| c.get(1))
+            .map(|m| parse_id_list(m.as_str()))
+        {
+            events.push(GameEvent::ReadSummary(ids));
+        }
+
+        if let Some(id) = INGAME_READ_REGEX
+            .find(line)
+            .and_then(|m| self.get_log_id(m.as_str()))
+        {
+            events.push(GameEvent::LogRead(id));
+        }
+
+        if let Some(local) = LEVEL_CHANGE_REGEX.captures(line).and_then(|c| c.get(1)) {
+            let local = local.as_str();
+            match self.levels.get_level_from_local(local) {
+                Some(level) => {
+                    self.level = Some(level);
+                    events.push(GameEvent::ExpeditionSelected(level));
+                }
+                None => {
+                    log::warn!("Unknown expedition '{local}'. The logs data may be out of date.");
+                    self.level = None;
+                    events.push(GameEvent::UnknownExpedition(local.to_string()));
+                }
+            }
+        }
+
+        if let Some(event) = GAME_STATE_REGEX
+            .captures(line)
+            .and_then(|c| c.get(1))
+            .and_then(|m| game_state_event(m.as_str()))
+        {
+            events.push(event);
+        }
+
+        events
+            .into_iter()
+            .map(|event| TimedEvent { time, event })
+            .collect()
+    }
+
+    pub fn parse_lines(&mut self, lines: impl IntoIterator<Item = String>) -> Vec<TimedEvent> {
+        lines
+            .into_iter()
+            .flat_map(|line| self.parse_line(&line))
+            .collect()
+    }
+
+    fn get_log_id(&self, name: &str) -> Option<u32> {
+        match game_data::get_id_from_name(name, self.level(), self.logs) {
+            NameLookup::Unique(id) => Some(id),
+            NameLookup::Ambiguous(ids) => {
+                log::warn!(
+                    "Log name '{name}' is shared by logs {ids:?} and can't be resolved in level {}",
+                    self.level()
+                        .map_or("unknown".to_string(), |l| l.to_string())
+                );
+                None
+            }
+            NameLookup::Unknown => None,
+        }
+    }
+}
+
+fn game_state_event(state: &str) -> Option<GameEvent> {
+    match state {
+        "InLevel" => Some(GameEvent::ExpeditionStarted),
+        "ExpeditionSuccess" => Some(GameEvent::ExpeditionSucceeded),
+        "ExpeditionFail" => Some(GameEvent::ExpeditionFailed),
+        "Lobby" => Some(GameEvent::ReturnedToLobby),
+        _ => None,
+    }
+}
+
+fn parse_time(line: &str) -> Option<Time> {
+    let c = TIMESTAMP_REGEX.captures(line)?;
+    let part = |i: usize| c.get(i).and_then(|m| m.as_str().parse::<u16>().ok());
+
+    Time::from_hms_milli(part(1)? as u8, part(2)? as u8, part(3)? as u8, part(4)?).ok()
+}
+
+fn parse_id_list(ids: &str) -> HashSet<u32> {
+    ids.split(',')
+        .filter_map(|id| id.trim().parse::<u32>().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    mod event_parser {
+        use crate::game_data::LevelMap;
+        use crate::game_events::{EventParser, GameEvent};
+        use crate::level_id::LevelId;
+        use crate::test_support::story_log;
+        use once_cell::sync::Lazy;
+        use std::collections::HashSet;
+        use time::Time;
+
+        static LEVELS: Lazy<LevelMap> = Lazy::new(LevelMap::built_in);
+
+        fn events(parser: &mut EventParser, lines: &[&str]) -> Vec<GameEvent> {
+            parser
+                .parse_lines(lines.iter().map(|s| s.to_string()))
+                .into_iter()
+                .map(|e| e.event)
+                .collect()
+        }
+
+        #[test]
+        fn returns_every_read_in_order() {
+            let logs = vec![
+                story_log(1, "AAA-BBB-CCC", &["R1A1"]),
+                story_log(2, "DDD-EEE-FFF", &["R1A1"]),
+            ];
+            let mut parser = EventParser::new(&logs, &LEVELS);
+
+            let events = events(&mut parser, &["DDD-EEE-FFF", "unrelated", "AAA-BBB-CCC"]);
+
+            assert_eq!(events, vec![GameEvent::LogRead(2), GameEvent::LogRead(1)]);
+        }
+
+        #[test]
+        fn parses_timestamp() {
+            let logs = vec![story_log(1, "AAA-BBB-CCC", &["R1A1"])];
+            let mut parser = EventParser::new(&logs, &LEVELS);
+
+            let events = parser.parse_line("00:25:31.042 - Reading AAA-BBB-CCC");
+
+            assert_eq!(
+                events[0].time,
+                Some(Time::from_hms_milli(0, 25, 31, 42).unwrap())
+            );
+        }
+
+        #[test]
+        fn lines_without_timestamp() {
+            let logs = vec![story_log(1, "AAA-BBB-CCC", &["R1A1"])];
+            let mut parser = EventParser::new(&logs, &LEVELS);
+
+            let events = parser.parse_line("AAA-BBB-CCC");
+
+            assert_eq!(events[0].time, None);
+            assert_eq!(events[0].event, GameEvent::LogRead(1));
+        }
+
+        #[test]
+        fn parses_expedition_lifecycle() {
+            let mut parser = EventParser::new(&[], &LEVELS);
+
+            let events = events(
+                &mut parser,
+                &[
+                    "SelectActiveExpedition : Local_32,1,0",
+                    "GameStateManager.ChangeState, from ReadyToStartLevel to InLevel",
+                    "GameStateManager.ChangeState, from InLevel to ExpeditionSuccess",
+                    "GameStateManager.ChangeState, from InLevel to ExpeditionFail",
+                    "GameStateManager.ChangeState, from AfterLevel to Lobby",
+                ],
+            );
+
+            assert_eq!(
+                events,
+                vec![
+                    GameEvent::ExpeditionSelected(LevelId::new(1, 'A', 1)),
+                    GameEvent::ExpeditionStarted,
+                    GameEvent::ExpeditionSucceeded,
+                    GameEvent::ExpeditionFailed,
+                    GameEvent::ReturnedToLobby,
+                ]
+            );
+        }
+
+        #[test]
+        fn parses_upper_case_state_changes() {
+            let mut parser = EventParser::new(&[], &LEVELS);
+
+            let events = events(
+                &mut parser,
+                &["00:31:02.193 - GAMESTATEMANAGER CHANGE STATE FROM : ReadyToStartLevel TO: InLevel"],
+            );
+
+            assert_eq!(events, vec![GameEvent::ExpeditionStarted]);
+        }
+
+        #[test]
+        fn ignores_other_game_states() {
+            let mut parser = EventParser::new(&[], &LEVELS);
+
+            let events = events(
+                &mut parser,
+                &["GameStateManager.ChangeState, from Lobby to Generating"],
+            );
+
+            assert!(events.is_empty());
+        }
+
+        #[test]
+        fn reports_unknown_expedition() {
+            let logs = vec![
+                story_log(1, "2MD-N3H-SYH", &["R7D1"]),
+                story_log(2, "2MD-N3H-SYH", &["R1B1"]),
+            ];
+            let mut parser = EventParser::new(&logs, &LEVELS);
+
+            let events = events(
+                &mut parser,
+                &[
+                    "SelectActiveExpedition : Local_32,2,0",
+                    "SelectActiveExpedition : Local_99,1,0",
+                    "2MD-N3H-SYH",
+                ],
+            );
+
+            assert_eq!(
+                events,
+                vec![
+                    GameEvent::ExpeditionSelected(LevelId::new(1, 'B', 1)),
+                    GameEvent::UnknownExpedition("Local_99,1,0".to_string())
+                ]
+            );
+            assert_eq!(parser.level(), None);
+        }
+
+        #[test]
+        fn parses_read_summary() {
+            let mut parser = EventParser::new(&[], &LEVELS);
+
+            let events = events(&mut parser, &["Logs Read: 2 / 10 | IDs: [123, 456]"]);
+
+            assert_eq!(
+                events,
+                vec![GameEvent::ReadSummary(HashSet::from([123, 456]))]
+            );
+        }
+
+        #[test]
+        fn uses_selected_level_for_shared_names() {
+            let logs = vec![
+                story_log(1, "2MD-N3H-SYH", &["R7D1"]),
+                story_log(2, "2MD-N3H-SYH", &["R1B1"]),
+            ];
+            let mut parser = EventParser::new(&logs, &LEVELS);
+
+            let events = events(
+                &mut parser,
+                &[
+                    "2MD-N3H-SYH",
+                    "SelectActiveExpedition : Local_32,2,0",
+                    "2MD-N3H-SYH",
+                ],
+            );
+
+            assert_eq!(
+                events,
+                vec![
+                    GameEvent::ExpeditionSelected(LevelId::new(1, 'B', 1)),
+                    GameEvent::LogRead(2)
+                ]
+            );
+            assert_eq!(parser.level(), Some(LevelId::new(1, 'B', 1)));
+        }
+    }
+}
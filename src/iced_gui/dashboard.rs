@@ -0,0 +1,196 @@
+use crate::game_data::StoryLog;
+use crate::iced_gui::Message;
+use crate::level_id::{LevelId, LevelScope};
+use iced::widget::{button, column, container, progress_bar, row, scrollable, text};
+use iced::{theme, Alignment, Element, Length, Renderer, Theme};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub read: usize,
+    pub total: usize,
+}
+
+impl Progress {
+    fn add(&mut self, read: bool) {
+        self.total += 1;
+        if read {
+            self.read += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RundownProgress {
+    pub rundown: u8,
+    pub progress: Progress,
+    pub tiers: Vec<TierProgress>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierProgress {
+    pub tier: char,
+    pub progress: Progress,
+    pub levels: Vec<(LevelId, Progress)>,
+}
+
+/// Counts read logs per rundown, tier and expedition. A log found in several zones or levels is
+/// counted once in each group it appears in.
+pub fn summarize(all_logs: &[StoryLog], read_log_ids: &HashSet<u32>) -> Vec<RundownProgress> {
+    let mut rundowns: BTreeMap<u8, Progress> = BTreeMap::new();
+    let mut tiers: BTreeMap<(u8, char), Progress> = BTreeMap::new();
+    let mut levels: BTreeMap<LevelId, Progress> = BTreeMap::new();
+
+    for log in all_logs {
+        let read = read_log_ids.contains(&log.id);
+        let log_levels: BTreeSet<LevelId> = log.locations.iter().map(|l| l.level).collect();
+        let log_tiers: BTreeSet<(u8, char)> =
+            log_levels.iter().map(|l| (l.rundown, l.tier)).collect();
+        let log_rundowns: BTreeSet<u8> = log_levels.iter().map(|l| l.rundown).collect();
+
+        log_levels
+            .into_iter()
+            .for_each(|l| levels.entry(l).or_default().add(read));
+        log_tiers
+            .into_iter()
+            .for_each(|t| tiers.entry(t).or_default().add(read));
+        log_rundowns
+            .into_iter()
+            .for_each(|r| rundowns.entry(r).or_default().add(read));
+    }
+
+    rundowns
+        .into_iter()
+        .map(|(rundown, progress)| RundownProgress {
+            rundown,
+            progress,
+            tiers: tiers
+                .range((rundown, char::MIN)..=(rundown, char::MAX))
+                .map(|(&(_, tier), &progress)| TierProgress {
+                    tier,
+                    progress,
+                    levels: levels
+                        .iter()
+                        .filter(|(l, _)| l.rundown == rundown && l.tier == tier)
+                        .map(|(&l, &p)| (l, p))
+                        .collect(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn view<'a>(
+    all_logs: &[StoryLog],
+    read_log_ids: &HashSet<u32>,
+) -> Element<'a, Message, Renderer<Theme>> {
+    let rows = summarize(all_logs, read_log_ids)
+        .into_iter()
+        .flat_map(|rundown| {
+            let tiers = rundown.tiers.into_iter().flat_map(move |tier| {
+                let levels = tier.levels.into_iter().map(|(level, progress)| {
+                    progress_row(level.to_string(), 40.0, LevelScope::Level(level), progress)
+                });
+                std::iter::once(progress_row(
+                    format!("Tier {}", tier.tier),
+                    20.0,
+                    LevelScope::Tier {
+                        rundown: rundown.rundown,
+                        tier: tier.tier,
+                    },
+                    tier.progress,
+                ))
+                .chain(levels)
+            });
+            std::iter::once(progress_row(
+                format!("Rundown {}", rundown.rundown),
+                0.0,
+                LevelScope::Rundown(rundown.rundown),
+                rundown.progress,
+            ))
+            .chain(tiers)
+        });
+
+    scrollable(column(rows.collect()).spacing(2).padding(5))
+        .height(Length::Fill)
+        .into()
+}
+
+/// A clickable row that narrows the log table to `scope`
+fn progress_row<'a>(
+    label: String,
+    indent: f32,
+    scope: LevelScope,
+    progress: Progress,
+) -> Element<'a, Message, Renderer<Theme>> {
+    button(
+        row![
+            container(text(label))
+                .padding([0.0, 0.0, 0.0, indent])
+                .width(120),
+            progress_bar(0.0..=progress.total as f32, progress.read as f32).height(12),
+            container(text(format!("{}/{}", progress.read, progress.total))).width(70),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center),
+    )
+    .on_press(Message::FilterLevel(scope))
+    .style(theme::Button::Text)
+    .width(Length::Fill)
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    mod summarize {
+        use crate::game_data::StoryLog;
+        use crate::iced_gui::dashboard::{summarize, Progress};
+        use crate::level_id::LevelId;
+        use crate::test_support::story_log;
+        use std::collections::HashSet;
+
+        fn progress(read: usize, total: usize) -> Progress {
+            Progress { read, total }
+        }
+
+        fn logs() -> Vec<StoryLog> {
+            vec![
+                story_log(1, "AAA-BBB-CCC", &["R1A1"]),
+                story_log(2, "AAA-BBB-CCC", &["R1A1", "R1B1"]),
+                story_log(3, "AAA-BBB-CCC", &["R1B2"]),
+                story_log(4, "AAA-BBB-CCC", &["R2A1", "R1A1"]),
+            ]
+        }
+
+        #[test]
+        fn counts_each_group() {
+            let summary = summarize(&logs(), &HashSet::from([1, 2]));
+
+            assert_eq!(summary.len(), 2);
+            let r1 = &summary[0];
+            assert_eq!(r1.rundown, 1);
+            assert_eq!(r1.progress, progress(2, 4));
+            assert_eq!(r1.tiers[0].tier, 'A');
+            assert_eq!(r1.tiers[0].progress, progress(2, 3));
+            assert_eq!(r1.tiers[1].tier, 'B');
+            assert_eq!(r1.tiers[1].progress, progress(1, 2));
+            assert_eq!(
+                r1.tiers[1].levels,
+                vec![
+                    (LevelId::new(1, 'B', 1), progress(1, 1)),
+                    (LevelId::new(1, 'B', 2), progress(0, 1))
+                ]
+            );
+            assert_eq!(summary[1].progress, progress(0, 1));
+        }
+
+        #[test]
+        fn counts_log_once_per_level() {
+            let logs = vec![story_log(1, "AAA-BBB-CCC", &["R1A1", "R1A1"])];
+
+            let summary = summarize(&logs, &HashSet::new());
+
+            assert_eq!(summary[0].tiers[0].levels[0].1, progress(0, 1));
+        }
+    }
+}
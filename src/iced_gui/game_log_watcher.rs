@@ -1,54 +1,72 @@
-use crate::game_data::StoryLog;
-use crate::{
-    file_contains_log_ids, try_get_log_id, try_get_new_level, INGAME_READ_REGEX, LEVEL_CHANGE_REGEX,
-};
+use crate::game_data::{LevelMap, StoryLog};
+use crate::game_events::{EventParser, GameEvent};
+use crate::level_id::LevelId;
+use crate::{file_contains_log_ids, WatchMode};
 use futures::{
     channel::mpsc::{channel, Receiver},
     SinkExt, StreamExt,
 };
 use iced::{subscription, Subscription};
-use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use notify::{Event, EventKind, PollWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+type BoxedWatcher = Box<dyn Watcher + Send>;
 
-fn async_watcher() -> notify::Result<(RecommendedWatcher, Receiver<notify::Result<Event>>)> {
+fn async_watcher(
+    mode: WatchMode,
+    poll_interval: Duration,
+) -> notify::Result<(BoxedWatcher, Receiver<notify::Result<Event>>)> {
     let (mut tx, rx) = channel(1);
-    let watcher = notify::recommended_watcher(move |res| {
+    let handler = move |res| {
         futures::executor::block_on(async {
             tx.send(res).await.unwrap();
         })
-    })?;
+    };
+    let watcher: BoxedWatcher = match mode {
+        WatchMode::Poll => Box::new(PollWatcher::new(
+            handler,
+            notify::Config::default().with_poll_interval(poll_interval),
+        )?),
+        _ => Box::new(notify::recommended_watcher(handler)?),
+    };
 
     Ok((watcher, rx))
 }
 
-pub enum GameEvent {
-    LogRead(u32),
-    LevelSelected(String),
-}
-
 enum State {
     NotWatching,
-    Watching(RecommendedWatcher, Receiver<notify::Result<Event>>),
+    Watching(BoxedWatcher, Receiver<notify::Result<Event>>),
     Failed(anyhow::Error),
 }
 
-pub fn watch(path: PathBuf, logs: Vec<StoryLog>) -> Subscription<GameEvent> {
+pub fn watch(
+    path: PathBuf,
+    logs: Vec<StoryLog>,
+    levels: LevelMap,
+    mode: WatchMode,
+    poll_interval: Duration,
+) -> Subscription<GameEvent> {
     struct Watch;
 
     subscription::channel(
         std::any::TypeId::of::<Watch>(),
         100,
-        |mut output| async move {
+        move |mut output| async move {
             let mut state = State::NotWatching;
-            let mut latest_id = None;
+            let mut tail = LogTail::default();
+            let mut reported_ids = HashSet::new();
             let mut latest_level = None;
+            let mut parser = EventParser::new(&logs, &levels);
 
             loop {
                 match state {
                     State::NotWatching => {
-                        state = async_watcher()
+                        state = async_watcher(mode, poll_interval)
                             .and_then(|(mut watcher, rx)| {
                                 watcher.watch(&path, RecursiveMode::NonRecursive)?;
                                 log::debug!("Watching '{}' for changes", path.display());
@@ -58,37 +76,34 @@ pub fn watch(path: PathBuf, logs: Vec<StoryLog>) -> Subscription<GameEvent> {
                     }
                     State::Watching(ref _watcher, ref mut rx) => match rx.next().await {
                         Some(Ok(Event {
-                            kind: EventKind::Create(_) | EventKind::Modify(_),
+                            kind: kind @ (EventKind::Create(_) | EventKind::Modify(_)),
                             paths,
                             ..
                         })) => {
                             if let Some(path) = paths.first() {
-                                let (id, level) = get_latest_data(path, &logs);
-
-                                if let (Some(new_id), true) = (id, id != latest_id) {
-                                    log::info!("new read log {new_id}");
-                                    let _ = output.send(GameEvent::LogRead(new_id)).await;
-                                    latest_id = id;
-                                }
-
-                                if level != latest_level {
-                                    if let Some(ref new_level) = level {
-                                        log::info!("new level selected {new_level}");
-                                        let _ = output
-                                            .send(GameEvent::LevelSelected(new_level.clone()))
-                                            .await;
-                                        latest_level = level;
+                                let created = matches!(kind, EventKind::Create(_));
+                                for event in get_new_events(&mut tail, path, created, &mut parser) {
+                                    if is_new_event(&event, &mut reported_ids, &mut latest_level) {
+                                        let _ = output.send(event).await;
                                     }
                                 }
                             }
                         }
+                        Some(Ok(Event {
+                            kind: EventKind::Remove(_),
+                            paths,
+                            ..
+                        })) => paths.iter().for_each(|p| tail.forget(p)),
                         Some(Err(e)) => {
                             log::error!("Failed to read file change - {e:?}");
                         }
-                        _ => {}
+                        Some(_) => {}
+                        None => state = State::Failed(anyhow::anyhow!("The file watcher stopped")),
                     },
                     State::Failed(ref e) => {
-                        log::error!("Unable to watch '{}' for changes - {:?}", path.display(), e)
+                        log::error!("Unable to watch '{}' for changes - {:?}", path.display(), e);
+                        // Nothing more will be reported, so wait forever instead of retrying
+                        futures::future::pending::<()>().await;
                     }
                 }
             }
@@ -96,29 +111,175 @@ pub fn watch(path: PathBuf, logs: Vec<StoryLog>) -> Subscription<GameEvent> {
     )
 }
 
-fn get_latest_data(path: &Path, all_logs: &[StoryLog]) -> (Option<u32>, Option<String>) {
-    let mut latest_id: Option<u32> = None;
-    let mut latest_level: Option<String> = None;
+/// Filters out reads that were already reported and repeated expedition selections
+fn is_new_event(
+    event: &GameEvent,
+    reported_ids: &mut HashSet<u32>,
+    latest_level: &mut Option<LevelId>,
+) -> bool {
+    match event {
+        GameEvent::LogRead(id) => {
+            let is_new = reported_ids.insert(*id);
+            if is_new {
+                log::info!("new read log {id}");
+            }
+            is_new
+        }
+        GameEvent::ReadSummary(ids) => {
+            log::info!("read summary with {} logs", ids.len());
+            reported_ids.extend(ids);
+            true
+        }
+        GameEvent::ExpeditionSelected(level) => {
+            let is_new = *latest_level != Some(*level);
+            if is_new {
+                log::info!("new level selected {level}");
+                *latest_level = Some(*level);
+            }
+            is_new
+        }
+        event => {
+            log::info!("{event:?}");
+            true
+        }
+    }
+}
+
+/// Tracks how far each watched file has been read so only appended bytes get parsed
+#[derive(Default)]
+struct LogTail {
+    offsets: HashMap<PathBuf, u64>,
+}
+
+impl LogTail {
+    /// Reads the complete lines appended to `path` since the last call. Starts over from the
+    /// beginning if the file was (re)created or has been truncated.
+    fn read_new_lines(&mut self, path: &Path, created: bool) -> io::Result<Vec<String>> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let offset = self.offsets.entry(path.to_path_buf()).or_insert(0);
+
+        if created || len < *offset {
+            log::debug!("'{}' was replaced, reading from start", path.display());
+            *offset = 0;
+        }
+
+        file.seek(SeekFrom::Start(*offset))?;
+        let mut buf = Vec::new();
+        file.take(len - *offset).read_to_end(&mut buf)?;
+
+        // Leave partially written lines for the next change event
+        let complete = buf.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+        *offset += complete as u64;
+
+        Ok(String::from_utf8_lossy(&buf[..complete])
+            .lines()
+            .map(String::from)
+            .collect())
+    }
+
+    fn forget(&mut self, path: &Path) {
+        self.offsets.remove(path);
+    }
+}
+
+fn get_new_events(
+    tail: &mut LogTail,
+    path: &Path,
+    created: bool,
+    parser: &mut EventParser,
+) -> Vec<GameEvent> {
     let should_check_file = path
         .file_name()
         .map(|s| file_contains_log_ids(&s.to_string_lossy()))
         .unwrap_or(false);
 
-    if should_check_file {
-        if let Ok(log_file) = File::open(path) {
-            let lines = BufReader::new(log_file).lines().map_while(Result::ok);
+    if !should_check_file {
+        return Vec::new();
+    }
 
-            for line in lines {
-                let line = line.as_str();
-                if let Some(m) = INGAME_READ_REGEX.find(line) {
-                    latest_id = try_get_log_id(&m, all_logs);
-                }
-                if let Some(m) = LEVEL_CHANGE_REGEX.captures(line).and_then(|c| c.get(1)) {
-                    latest_level = try_get_new_level(&m);
-                }
-            }
+    match tail.read_new_lines(path, created) {
+        Ok(lines) => parser
+            .parse_lines(lines)
+            .into_iter()
+            .map(|timed| timed.event)
+            .collect(),
+        Err(e) => {
+            log::warn!("Couldn't read '{}' - {e}", path.display());
+            Vec::new()
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    mod log_tail {
+        use crate::iced_gui::game_log_watcher::LogTail;
+        use crate::test_support::{test_dir, TestDir};
+        use std::fs;
+        use std::io::Write;
+        use std::path::{Path, PathBuf};
+
+        fn test_file(name: &str, contents: &str) -> (TestDir, PathBuf) {
+            let dir = test_dir(&format!("tail-{name}"));
+            let path = dir.join("Player.log");
+            fs::write(&path, contents).unwrap();
+            (dir, path)
+        }
+
+        fn append(path: &Path, contents: &str) {
+            let mut file = fs::OpenOptions::new().append(true).open(path).unwrap();
+            file.write_all(contents.as_bytes()).unwrap();
+        }
+
+        #[test]
+        fn only_reads_appended_lines() {
+            let (_dir, path) = test_file("appended", "one\ntwo\n");
+            let mut tail = LogTail::default();
+
+            let first = tail.read_new_lines(&path, false).unwrap();
+            append(&path, "three\n");
+            let second = tail.read_new_lines(&path, false).unwrap();
 
-    (latest_id, latest_level)
+            assert_eq!(first, vec!["one", "two"]);
+            assert_eq!(second, vec!["three"]);
+        }
+
+        #[test]
+        fn waits_for_partial_lines_to_complete() {
+            let (_dir, path) = test_file("partial", "one\ntw");
+            let mut tail = LogTail::default();
+
+            let first = tail.read_new_lines(&path, false).unwrap();
+            append(&path, "o\n");
+            let second = tail.read_new_lines(&path, false).unwrap();
+
+            assert_eq!(first, vec!["one"]);
+            assert_eq!(second, vec!["two"]);
+        }
+
+        #[test]
+        fn starts_over_if_truncated() {
+            let (_dir, path) = test_file("truncated", "one\ntwo\n");
+            let mut tail = LogTail::default();
+
+            tail.read_new_lines(&path, false).unwrap();
+            fs::write(&path, "new\n").unwrap();
+            let lines = tail.read_new_lines(&path, false).unwrap();
+
+            assert_eq!(lines, vec!["new"]);
+        }
+
+        #[test]
+        fn starts_over_if_created() {
+            let (_dir, path) = test_file("created", "one\n");
+            let mut tail = LogTail::default();
+
+            tail.read_new_lines(&path, false).unwrap();
+            fs::write(&path, "one\ntwo\n").unwrap();
+            let lines = tail.read_new_lines(&path, true).unwrap();
+
+            assert_eq!(lines, vec!["one", "two"]);
+        }
+    }
 }
@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Window and table layout remembered between launches
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GuiState {
+    pub window_size: (u32, u32),
+    pub window_position: Option<(i32, i32)>,
+    pub column_widths: Vec<f32>,
+    /// Ignored if set in the config, which takes precedence
+    pub hide_read: bool,
+    pub auto_filter: bool,
+    pub filter: String,
+    pub show_audio: bool,
+    pub audio_only: bool,
+}
+
+impl Default for GuiState {
+    fn default() -> Self {
+        Self {
+            window_size: (500, 600),
+            window_position: None,
+            column_widths: Vec::new(),
+            hide_read: false,
+            auto_filter: true,
+            filter: String::new(),
+            show_audio: false,
+            audio_only: false,
+        }
+    }
+}
+
+impl GuiState {
+    pub fn default_path() -> Result<PathBuf> {
+        crate::state_dir().map(|dir| dir.join("gui.json"))
+    }
+
+    /// Loads the saved state, falling back to defaults if there isn't any or it can't be read
+    pub fn load_or_default(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+
+        fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| serde_json::from_str(&json).map_err(anyhow::Error::from))
+            .unwrap_or_else(|e| {
+                log::warn!("Couldn't load GUI state from '{}' - {e}", path.display());
+                Self::default()
+            })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Couldn't create directory '{}'", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+            .with_context(|| format!("Couldn't write GUI state '{}'", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod gui_state {
+        use crate::iced_gui::gui_state::GuiState;
+        use crate::test_support::test_dir;
+        use std::fs;
+
+        #[test]
+        fn survives_save_and_load() {
+            let dir = test_dir("gui-round-trip");
+            let path = dir.join("gui.json");
+            let state = GuiState {
+                window_size: (800, 900),
+                window_position: Some((10, -20)),
+                column_widths: vec![40.0, 75.5],
+                hide_read: true,
+                auto_filter: false,
+                filter: "R7".to_string(),
+                show_audio: true,
+                audio_only: false,
+            };
+
+            state.save(&path).unwrap();
+            let loaded = GuiState::load_or_default(&path);
+
+            assert_eq!(loaded, state);
+        }
+
+        #[test]
+        fn falls_back_to_default_if_invalid() {
+            let dir = test_dir("gui-invalid");
+            let path = dir.join("gui.json");
+            fs::write(&path, "not json").unwrap();
+
+            let loaded = GuiState::load_or_default(&path);
+
+            assert_eq!(loaded, GuiState::default());
+        }
+
+        #[test]
+        fn fills_missing_fields() {
+            let dir = test_dir("gui-partial");
+            let path = dir.join("gui.json");
+            fs::write(&path, r#"{ "filter": "R1" }"#).unwrap();
+
+            let loaded = GuiState::load_or_default(&path);
+
+            assert_eq!(loaded.filter, "R1");
+            assert_eq!(loaded.window_size, GuiState::default().window_size);
+        }
+    }
+}
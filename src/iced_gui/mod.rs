@@ -1,95 +1,127 @@
+mod dashboard;
 mod game_log_watcher;
+mod gui_state;
 
-use crate::game_data::StoryLog;
-use crate::iced_gui::game_log_watcher::GameEvent;
-use crate::{get_logs, Options};
+use crate::export::{default_export_path, export_to_file, ExportFormat};
+use crate::game_data::{map_log_to_rows, LevelMap, Location, LogRow, StoryLog};
+use crate::game_events::GameEvent;
+use crate::iced_gui::gui_state::GuiState;
+use crate::level_id::{LevelId, LevelScope};
+use crate::play_fab::PlayFabError;
+use crate::progress::{ProgressStore, ReadRecord, ReadSource};
+use crate::snapshot::describe_age;
+use crate::{get_logs, now_local, LoadedLogs, Options, WatchMode};
 use iced::alignment::Horizontal;
 use iced::widget::tooltip::Position;
 use iced::widget::{
-    checkbox, column, container, responsive, row, scrollable, text, text_input, tooltip,
-    Responsive, Text,
+    button, checkbox, column, container, pick_list, responsive, row, scrollable, text, text_input,
+    tooltip, Responsive, Text,
 };
 use iced::{
-    executor, font, theme, window, Alignment, Application, Command, Element, Font, Length,
-    Renderer, Settings, Subscription, Theme,
+    executor, font, subscription, theme, window, Alignment, Application, Color, Command, Element,
+    Event, Font, Length, Renderer, Settings, Subscription, Theme,
 };
 use iced_aw::Spinner;
 use iced_table::table;
-use std::collections::HashSet;
-use std::fmt::Write;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use time::OffsetDateTime;
 
 pub enum GtfoLogTracker {
     Loading,
-    Loaded(MainView),
+    Loaded(Box<MainView>),
     Error(String),
 }
 
+#[derive(Default)]
+pub struct Flags {
+    options: Options,
+    gui_state: GuiState,
+}
+
 impl GtfoLogTracker {
-    pub fn settings(options: Options) -> Settings<Options> {
+    pub fn settings(options: Options) -> Settings<Flags> {
         let icon = window::icon::from_file_data(
             include_bytes!("../../resources/icon.ico"),
             Some(image::ImageFormat::Ico),
         )
         .ok();
-        Settings::<Options> {
+        let gui_state = GuiState::default_path()
+            .map(|path| GuiState::load_or_default(&path))
+            .unwrap_or_default();
+        Settings::<Flags> {
             window: window::Settings {
-                size: (500, 600),
+                size: gui_state.window_size,
+                position: gui_state
+                    .window_position
+                    .map(|(x, y)| window::Position::Specific(x, y))
+                    .unwrap_or_default(),
                 icon,
                 ..window::Settings::default()
             },
-            flags: options,
+            flags: Flags { options, gui_state },
             default_font: Default::default(),
-            ..Settings::<Options>::default()
+            // Window state is saved before closing
+            exit_on_close_request: false,
+            ..Settings::<Flags>::default()
         }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct LoadedData {
+    logs: LoadedLogs,
+    options: Options,
+    gui_state: GuiState,
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
-    DataLoaded(Vec<StoryLog>, HashSet<u32>, PathBuf),
+    DataLoaded(Box<LoadedData>),
     SyncHeader(scrollable::AbsoluteOffset),
     TableResizing(usize, f32),
     TableResized,
     ToggleHideRead(bool),
     ToggleAutoFilter(bool),
+    ToggleShowAudio(bool),
+    ToggleDashboard,
+    /// Shows the log table filtered to a rundown, tier or level picked on the dashboard
+    FilterLevel(LevelScope),
+    ClearLevelFilter,
+    ToggleAudioOnly(bool),
     FilterChanged(String),
     FontLoaded(Result<(), font::Error>),
     Error(String),
-    NewLogRead(u32),
-    LevelChanged(String),
-}
-
-impl From<GameEvent> for Message {
-    fn from(value: GameEvent) -> Self {
-        match value {
-            GameEvent::LogRead(id) => Message::NewLogRead(id),
-            GameEvent::LevelSelected(name) => Message::LevelChanged(name),
-        }
-    }
+    Game(GameEvent),
+    MarkRead(u32),
+    Export(ExportFormat),
+    WindowResized(u32, u32),
+    WindowMoved(i32, i32),
+    CloseRequested,
 }
 
 impl Application for GtfoLogTracker {
     type Executor = executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = Options;
+    type Flags = Flags;
 
-    fn new(options: Self::Flags) -> (Self, Command<Self::Message>) {
+    fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
+        let Flags { options, gui_state } = flags;
         (
             GtfoLogTracker::Loading,
             Command::batch(vec![
                 font::load(include_bytes!("../../resources/icons.ttf").as_slice())
                     .map(Message::FontLoaded),
-                Command::perform(
-                    get_logs(options.gtfo_path.clone(), options.use_playfab),
-                    |r| {
-                        r.map(|(all_logs, read_log_ids)| {
-                            Message::DataLoaded(all_logs, read_log_ids, options.gtfo_path)
-                        })
-                        .unwrap_or_else(|e| Message::Error(e.to_string()))
-                    },
-                ),
+                Command::perform(get_logs(options.clone()), |r| {
+                    r.map(|logs| {
+                        Message::DataLoaded(Box::new(LoadedData {
+                            logs,
+                            options,
+                            gui_state,
+                        }))
+                    })
+                    .unwrap_or_else(|e| Message::Error(e.to_string()))
+                }),
             ]),
         )
     }
@@ -100,15 +132,41 @@ impl Application for GtfoLogTracker {
 
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
         match message {
-            Message::DataLoaded(all_logs, read_log_ids, gtfo_path) => {
-                let view = MainView::new(all_logs, read_log_ids, gtfo_path);
-                *self = GtfoLogTracker::Loaded(view);
+            Message::DataLoaded(data) => {
+                let LoadedData {
+                    logs,
+                    options,
+                    gui_state,
+                } = *data;
+                let view = MainView::new(logs, options, gui_state);
+                *self = GtfoLogTracker::Loaded(Box::new(view));
             }
-            Message::NewLogRead(log_id) => {
+            Message::Game(event) => {
                 if let GtfoLogTracker::Loaded(view) = self {
-                    if !view.read_log_ids.contains(&log_id) {
-                        view.read_log_ids.insert(log_id);
-                    }
+                    view.handle_game_event(event);
+                }
+            }
+            Message::MarkRead(log_id) => {
+                if let GtfoLogTracker::Loaded(view) = self {
+                    view.record_reads(HashMap::from([(
+                        log_id,
+                        ReadRecord::now(ReadSource::Manual, None),
+                    )]));
+                }
+            }
+            Message::Export(format) => {
+                if let GtfoLogTracker::Loaded(view) = self {
+                    let result = default_export_path(format, now_local()).and_then(|path| {
+                        export_to_file(&view.all_logs, &view.read_log_ids, format, &path)
+                            .map(|_| path)
+                    });
+                    view.status = match result {
+                        Ok(path) => Some(format!("Exported to {}", path.display())),
+                        Err(e) => {
+                            log::error!("Export failed - {e:?}");
+                            Some(format!("Export failed - {e}"))
+                        }
+                    };
                 }
             }
             Message::SyncHeader(offset) => {
@@ -142,17 +200,57 @@ impl Application for GtfoLogTracker {
                     view.auto_filter = hide
                 }
             }
+            Message::ToggleShowAudio(show) => {
+                if let GtfoLogTracker::Loaded(view) = self {
+                    view.show_audio = show
+                }
+            }
+            Message::ToggleAudioOnly(only) => {
+                if let GtfoLogTracker::Loaded(view) = self {
+                    view.audio_only = only
+                }
+            }
             Message::FilterChanged(text) => {
                 if let GtfoLogTracker::Loaded(view) = self {
                     view.filter = text;
                 }
             }
-            Message::LevelChanged(level_name) => {
+            Message::ToggleDashboard => {
+                if let GtfoLogTracker::Loaded(view) = self {
+                    view.show_dashboard = !view.show_dashboard;
+                }
+            }
+            Message::FilterLevel(scope) => {
+                if let GtfoLogTracker::Loaded(view) = self {
+                    view.level_scope = Some(scope);
+                    view.filter.clear();
+                    view.show_dashboard = false;
+                }
+            }
+            Message::ClearLevelFilter => {
+                if let GtfoLogTracker::Loaded(view) = self {
+                    view.level_scope = None;
+                }
+            }
+            Message::WindowResized(width, height) => {
+                if let GtfoLogTracker::Loaded(view) = self {
+                    view.window_size = (width, height);
+                }
+            }
+            Message::WindowMoved(x, y) => {
+                if let GtfoLogTracker::Loaded(view) = self {
+                    view.window_position = Some((x, y));
+                }
+            }
+            Message::CloseRequested => {
                 if let GtfoLogTracker::Loaded(view) = self {
-                    if view.auto_filter {
-                        view.filter = level_name;
+                    if let Err(e) =
+                        GuiState::default_path().and_then(|path| view.gui_state().save(&path))
+                    {
+                        log::error!("Couldn't save GUI state - {e:?}");
                     }
                 }
+                return window::close();
             }
             Message::Error(e) => {
                 log::error!("Error: {}", e);
@@ -171,18 +269,39 @@ impl Application for GtfoLogTracker {
                 column![text("Loading your progress"), Spinner::new()]
                     .align_items(Alignment::Center),
             ),
+            GtfoLogTracker::Loaded(view) if view.show_dashboard => layout(column![
+                header(view),
+                dashboard::view(&view.all_logs, &view.read_log_ids)
+            ]),
             GtfoLogTracker::Loaded(view) => layout(column![header(view), log_table(view)]),
             GtfoLogTracker::Error(e) => layout(text(e)),
         }
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        if let GtfoLogTracker::Loaded(view) = self {
-            game_log_watcher::watch(view.gtfo_path.clone(), view.all_logs.clone())
-                .map(Message::from)
-        } else {
-            Subscription::none()
-        }
+        let log_watcher = match self {
+            GtfoLogTracker::Loaded(view) if view.options.watch_mode != WatchMode::Off => {
+                game_log_watcher::watch(
+                    view.options.gtfo_path.clone(),
+                    view.all_logs.clone(),
+                    view.levels.clone(),
+                    view.options.watch_mode,
+                    view.options.poll_interval,
+                )
+                .map(Message::Game)
+            }
+            _ => Subscription::none(),
+        };
+        let window_events = subscription::events_with(|event, _| match event {
+            Event::Window(window::Event::Resized { width, height }) => {
+                Some(Message::WindowResized(width, height))
+            }
+            Event::Window(window::Event::Moved { x, y }) => Some(Message::WindowMoved(x, y)),
+            Event::Window(window::Event::CloseRequested) => Some(Message::CloseRequested),
+            _ => None,
+        });
+
+        Subscription::batch([log_watcher, window_events])
     }
 }
 
@@ -197,39 +316,87 @@ fn layout<'a>(
         .into()
 }
 
+/// Shows the selected expedition, warning when it isn't in the logs data
+fn current_level(view: &MainView) -> Text<'_, Renderer<Theme>> {
+    match (&view.current_level, &view.unknown_level) {
+        (_, Some(local)) => warning(format!(
+            "Unknown level ({local}), logs data may be outdated"
+        )),
+        (Some(level), None) => text(format!("Level {level}")).size(12),
+        (None, None) => text("").size(12),
+    }
+}
+
+fn warning(message: impl ToString) -> Text<'static, Renderer<Theme>> {
+    text(message)
+        .size(12)
+        .style(theme::Text::Color(Color::from_rgb(0.9, 0.6, 0.0)))
+}
+
+/// Explains why the log files and saved PlayFab snapshot, if there is one, were read instead
+/// of PlayFab
+fn play_fab_warning(error: &PlayFabError, snapshot: Option<OffsetDateTime>) -> String {
+    let reason = match error {
+        PlayFabError::Steam(_) => "Steam isn't running".to_string(),
+        PlayFabError::Timeout => "PlayFab didn't respond".to_string(),
+        PlayFabError::Network(_) => "Couldn't reach PlayFab".to_string(),
+        PlayFabError::Api(details) => format!("PlayFab error {}", details.error),
+        PlayFabError::InvalidResponse { status, .. } => {
+            format!("Unexpected response from PlayFab (HTTP {status})")
+        }
+    };
+
+    match snapshot {
+        Some(fetched_at) => format!(
+            "{reason}, using PlayFab data from {} and log files",
+            describe_age(fetched_at, OffsetDateTime::now_utc())
+        ),
+        None => format!("{reason}, progress read from log files"),
+    }
+}
+
 fn header(view: &MainView) -> Element<'_, Message, Renderer<Theme>> {
     row![
-        container(text(format!(
-            "{}/{} Read",
-            view.read_log_ids.len(),
-            view.all_logs.len()
-        )))
-        .align_x(Horizontal::Left)
-        .width(Length::FillPortion(1)),
         container(
-            text_input("Filter", view.filter.as_str())
-                .on_input(Message::FilterChanged)
-                .padding(10)
+            column![
+                text(format!(
+                    "{}/{} Read",
+                    view.read_log_ids.len(),
+                    view.all_logs.len()
+                )),
+                text(format!("Data {}", view.dataset_version)).size(12),
+                current_level(view),
+                warning(
+                    view.play_fab_error
+                        .as_ref()
+                        .map(|e| play_fab_warning(e, view.play_fab_snapshot))
+                        .unwrap_or_default()
+                ),
+                row![
+                    pick_list(&ExportFormat::ALL[..], None, Message::Export)
+                        .placeholder("Export")
+                        .text_size(12),
+                    button(
+                        text(if view.show_dashboard {
+                            "Logs"
+                        } else {
+                            "Dashboard"
+                        })
+                        .size(12)
+                    )
+                    .on_press(Message::ToggleDashboard),
+                ]
+                .spacing(5),
+                text(view.status.as_deref().unwrap_or_default()).size(12),
+            ]
+            .spacing(5)
         )
-        .width(Length::FillPortion(2)),
-        container(column![
-            option(
-                "Hide Read",
-                view.hide_read,
-                "Only show un-read logs",
-                Position::Left,
-                Message::ToggleHideRead
-            ),
-            option(
-                "Auto Filter",
-                view.auto_filter,
-                "When in-game, automatically filter logs to currently selected expedition",
-                Position::Bottom,
-                Message::ToggleAutoFilter
-            )
-        ])
-        .width(Length::FillPortion(1))
-        .align_x(Horizontal::Right)
+        .align_x(Horizontal::Left)
+        .width(Length::FillPortion(1)),
+        container(filter_input(view)).width(Length::FillPortion(2)),
+        container(column(options(view)))
+            .width(Length::FillPortion(1))
+            .align_x(Horizontal::Right)
     ]
     .padding(5)
     .spacing(10)
@@ -237,6 +404,68 @@ fn header(view: &MainView) -> Element<'_, Message, Renderer<Theme>> {
     .into()
 }
 
+/// The text filter, plus a button to clear the level picked on the dashboard if there is one
+fn filter_input(view: &MainView) -> Element<'_, Message, Renderer<Theme>> {
+    let input = text_input("Filter", view.filter.as_str())
+        .on_input(Message::FilterChanged)
+        .padding(10);
+
+    match view.level_scope {
+        Some(scope) => row![
+            tooltip(
+                button(text(format!("{scope} only")))
+                    .on_press(Message::ClearLevelFilter)
+                    .padding(10),
+                "Click to show every level",
+                Position::Bottom
+            )
+            .style(iced::theme::Container::Box),
+            input
+        ]
+        .spacing(5)
+        .into(),
+        None => input.into(),
+    }
+}
+
+fn options(view: &MainView) -> Vec<Element<'_, Message, Renderer<Theme>>> {
+    let mut options = vec![
+        option(
+            "Hide Read",
+            view.hide_read,
+            "Only show un-read logs",
+            Position::Left,
+            Message::ToggleHideRead,
+        ),
+        option(
+            "Auto Filter",
+            view.auto_filter,
+            "When in-game, automatically filter logs to currently selected expedition",
+            Position::Left,
+            Message::ToggleAutoFilter,
+        ),
+    ];
+    // Datasets without audio ids would only ever show an empty column
+    if view.has_audio {
+        options.push(option(
+            "Audio Column",
+            view.show_audio,
+            "Show the id of the sound that plays when a log is opened",
+            Position::Left,
+            Message::ToggleShowAudio,
+        ));
+        options.push(option(
+            "Audio Only",
+            view.audio_only,
+            "Only show logs that play a sound when opened",
+            Position::Bottom,
+            Message::ToggleAudioOnly,
+        ));
+    }
+
+    options
+}
+
 fn option<'a>(
     label: impl Into<String>,
     is_checked: bool,
@@ -256,7 +485,7 @@ fn option<'a>(
 
 fn log_table(view: &MainView) -> Responsive<'_, Message, Renderer<Theme>> {
     responsive(|size| {
-        let filtered_rows: Vec<Row> = view
+        let filtered_rows: Vec<TableRow> = view
             .all_logs
             .iter()
             .filter_map(
@@ -266,23 +495,25 @@ fn log_table(view: &MainView) -> Responsive<'_, Message, Renderer<Theme>> {
                 },
             )
             .flatten()
-            .filter(|r| {
-                if view.filter.is_empty() {
-                    true
-                } else {
-                    let f = view.filter.to_ascii_lowercase();
-                    r.level.to_ascii_lowercase().contains(&f)
-                        || r.name.to_ascii_lowercase().contains(&f)
-                        || r.zone.to_ascii_lowercase().contains(&f)
-                        || r.id.to_string().contains(&f)
-                }
+            .filter(|r| view.in_level_scope(&r.level))
+            .filter(|r| view.filter.is_empty() || r.matches(&view.filter))
+            .filter(|r| !(view.has_audio && view.audio_only) || r.audio_id.is_some())
+            .map(|log| TableRow {
+                status: read_status(view, log.id),
+                log,
             })
             .collect();
+        // The audio column is last so hiding it doesn't change the other columns' indices
+        let columns = if view.has_audio && view.show_audio {
+            &view.log_table.columns[..]
+        } else {
+            &view.log_table.columns[..AUDIO_COLUMN]
+        };
 
         table(
             view.log_table.header.clone(),
             view.log_table.body.clone(),
-            &view.log_table.columns,
+            columns,
             &filtered_rows,
             Message::SyncHeader,
         )
@@ -293,7 +524,7 @@ fn log_table(view: &MainView) -> Responsive<'_, Message, Renderer<Theme>> {
 }
 
 impl<'a, 'b> table::Column<'a, 'b, Message, Renderer> for TableColumn {
-    type Row = Row;
+    type Row = TableRow;
 
     fn header(&'b self, _: usize) -> Element<'a, Message, Renderer> {
         container(text(&self.title)).height(24).center_y().into()
@@ -305,15 +536,28 @@ impl<'a, 'b> table::Column<'a, 'b, Message, Renderer> for TableColumn {
         _: usize,
         row: &'b Self::Row,
     ) -> Element<'a, Message, Renderer> {
+        let TableRow { log: row, status } = row;
         match col_index {
-            0 => icon_read(row.read),
-            1 => text(&row.level),
-            2 => text(&row.zone),
-            3 => text(&row.name),
-            4 => text(row.id),
-            _ => text("?"),
+            0 if *status == ReadStatus::Unread => tooltip(
+                button(icon_read(*status))
+                    .on_press(Message::MarkRead(row.id))
+                    .style(theme::Button::Text)
+                    .padding(0),
+                "Mark as read",
+                Position::Right,
+            )
+            .style(theme::Container::Box)
+            .into(),
+            0 => tooltip(icon_read(*status), status.description(), Position::Right)
+                .style(theme::Container::Box)
+                .into(),
+            1 => text(row.level).into(),
+            2 => text(&row.zone).into(),
+            3 => text(&row.name).into(),
+            4 => text(row.id).into(),
+            AUDIO_COLUMN => text(row.audio_id.map(|id| id.to_string()).unwrap_or_default()).into(),
+            _ => text("?").into(),
         }
-        .into()
     }
 
     fn width(&self) -> f32 {
@@ -325,35 +569,7 @@ impl<'a, 'b> table::Column<'a, 'b, Message, Renderer> for TableColumn {
     }
 }
 
-fn map_log_to_rows<'a>(
-    log: &'a StoryLog,
-    read_log_ids: &'a HashSet<u32>,
-) -> impl Iterator<Item = Row> + 'a {
-    log.locations.iter().map(|loc| Row {
-        level: format!("R{}{}", loc.rundown, loc.level),
-        name: loc.name.to_string(),
-        id: log.id,
-        read: read_log_ids.contains(&log.id),
-        zone: if loc.zones == vec![0] {
-            "Outside".to_string()
-        } else {
-            comma_join(&loc.zones)
-        },
-    })
-}
-
-fn comma_join(nums: &[u16]) -> String {
-    nums.iter()
-        .enumerate()
-        .fold(String::new(), |mut output, (i, num)| {
-            if i == 0 {
-                let _ = write!(output, "{num}");
-            } else {
-                let _ = write!(output, ", {num}");
-            }
-            output
-        })
-}
+const AUDIO_COLUMN: usize = 5;
 
 const ICONS: Font = Font::with_name("gtfo-tracker-icons");
 fn icon(unicode: char, style: theme::Text) -> Text<'static> {
@@ -364,49 +580,197 @@ fn icon(unicode: char, style: theme::Text) -> Text<'static> {
         .horizontal_alignment(Horizontal::Center)
 }
 
-fn icon_read(read: bool) -> Text<'static> {
-    if read {
-        icon(
-            '\u{ea52}',
-            theme::Text::Color(Theme::Light.extended_palette().primary.base.color),
-        )
-    } else {
-        icon('\u{ea53}', theme::Text::Default)
+fn icon_read(status: ReadStatus) -> Text<'static> {
+    let palette = Theme::Light.extended_palette();
+    match status {
+        ReadStatus::Unread => icon('\u{ea53}', theme::Text::Default),
+        ReadStatus::ConfirmedByPlayFab => {
+            icon('\u{ea52}', theme::Text::Color(palette.primary.base.color))
+        }
+        ReadStatus::SeenInLog | ReadStatus::MarkedManually => {
+            icon('\u{ea52}', theme::Text::Color(palette.secondary.base.color))
+        }
+    }
+}
+
+/// How the tracker knows a log has been read
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ReadStatus {
+    Unread,
+    ConfirmedByPlayFab,
+    SeenInLog,
+    MarkedManually,
+}
+
+impl ReadStatus {
+    fn description(&self) -> &'static str {
+        match self {
+            ReadStatus::Unread => "Not read",
+            ReadStatus::ConfirmedByPlayFab => "Confirmed by PlayFab",
+            ReadStatus::SeenInLog => "Seen in log only",
+            ReadStatus::MarkedManually => "Marked as read manually",
+        }
+    }
+}
+
+fn read_status(view: &MainView, id: u32) -> ReadStatus {
+    if !view.read_log_ids.contains(&id) {
+        return ReadStatus::Unread;
+    }
+
+    match view.progress.get(id) {
+        Some(record) if record.confirmed_by_play_fab() => ReadStatus::ConfirmedByPlayFab,
+        Some(record) if record.source == ReadSource::Manual => ReadStatus::MarkedManually,
+        _ => ReadStatus::SeenInLog,
     }
 }
 
 pub struct MainView {
     all_logs: Vec<StoryLog>,
+    levels: LevelMap,
+    dataset_version: String,
     read_log_ids: HashSet<u32>,
+    progress: ProgressStore,
+    current_level: Option<LevelId>,
+    /// Game id of the selected expedition if it isn't in the level map
+    unknown_level: Option<String>,
+    play_fab_error: Option<PlayFabError>,
+    /// When the PlayFab snapshot used instead of PlayFab was fetched
+    play_fab_snapshot: Option<OffsetDateTime>,
+    status: Option<String>,
     hide_read: bool,
     auto_filter: bool,
+    /// Whether any log in the dataset has an audio id
+    has_audio: bool,
+    show_audio: bool,
+    audio_only: bool,
+    show_dashboard: bool,
+    /// Level picked on the dashboard, applied along with the text filter
+    level_scope: Option<LevelScope>,
     filter: String,
     log_table: Table,
-    gtfo_path: PathBuf,
+    options: Options,
+    window_size: (u32, u32),
+    window_position: Option<(i32, i32)>,
 }
 
 impl MainView {
-    fn new(all_logs: Vec<StoryLog>, read_log_ids: HashSet<u32>, gtfo_path: PathBuf) -> Self {
+    fn new(logs: LoadedLogs, options: Options, gui_state: GuiState) -> Self {
+        let LoadedLogs {
+            dataset,
+            progress,
+            play_fab_error,
+            play_fab_snapshot,
+        } = logs;
+        let mut columns = vec![
+            TableColumn::new("", 40.0),
+            TableColumn::new("Level", 60.0),
+            TableColumn::new("Zone", 90.0),
+            TableColumn::new("Name", 130.0),
+            TableColumn::new("Id", 130.0),
+            TableColumn::new("Audio", 60.0),
+        ];
+        columns
+            .iter_mut()
+            .zip(gui_state.column_widths)
+            .for_each(|(column, width)| column.width = width);
+
+        let has_audio = dataset
+            .logs
+            .iter()
+            .any(|l| l.locations.iter().any(Location::has_audio));
+
         Self {
-            all_logs,
-            read_log_ids,
-            gtfo_path,
-            hide_read: false,
-            auto_filter: true,
-            filter: "".to_string(),
+            dataset_version: dataset.to_string(),
+            all_logs: dataset.logs,
+            levels: dataset.levels,
+            read_log_ids: progress.read_ids(),
+            progress,
+            current_level: None,
+            unknown_level: None,
+            play_fab_error,
+            play_fab_snapshot,
+            status: None,
+            hide_read: options.hide_read.unwrap_or(gui_state.hide_read),
+            auto_filter: options.auto_filter.unwrap_or(gui_state.auto_filter),
+            has_audio,
+            show_audio: gui_state.show_audio,
+            audio_only: gui_state.audio_only,
+            show_dashboard: false,
+            level_scope: None,
+            options,
+            filter: gui_state.filter,
+            window_size: gui_state.window_size,
+            window_position: gui_state.window_position,
             log_table: Table {
-                columns: vec![
-                    TableColumn::new("", 40.0),
-                    TableColumn::new("Level", 60.0),
-                    TableColumn::new("Zone", 90.0),
-                    TableColumn::new("Name", 130.0),
-                    TableColumn::new("Id", 130.0),
-                ],
+                columns,
                 header: scrollable::Id::unique(),
                 body: scrollable::Id::unique(),
             },
         }
     }
+
+    fn gui_state(&self) -> GuiState {
+        GuiState {
+            window_size: self.window_size,
+            window_position: self.window_position,
+            column_widths: self.log_table.columns.iter().map(|c| c.width).collect(),
+            hide_read: self.hide_read,
+            auto_filter: self.auto_filter,
+            filter: self.filter.clone(),
+            show_audio: self.show_audio,
+            audio_only: self.audio_only,
+        }
+    }
+
+    fn in_level_scope(&self, level: &LevelId) -> bool {
+        match self.level_scope {
+            Some(scope) => scope.contains(level),
+            None => true,
+        }
+    }
+
+    fn record_reads(&mut self, reads: HashMap<u32, ReadRecord>) {
+        self.read_log_ids.extend(reads.keys());
+        if self.progress.merge(reads) > 0 {
+            if let Err(e) = self.progress.save() {
+                log::error!("Couldn't save progress - {e:?}");
+            }
+        }
+    }
+
+    fn handle_game_event(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::LogRead(id) => {
+                let level = self.current_level;
+                self.record_reads(HashMap::from([(
+                    id,
+                    ReadRecord::now(ReadSource::LogFile, level),
+                )]));
+            }
+            GameEvent::ReadSummary(ids) => self.record_reads(
+                ids.into_iter()
+                    .map(|id| (id, ReadRecord::now(ReadSource::LogFile, None)))
+                    .collect(),
+            ),
+            GameEvent::ExpeditionSelected(level) => {
+                if self.auto_filter {
+                    self.filter = level.to_string();
+                    self.level_scope = None;
+                }
+                self.current_level = Some(level);
+                self.unknown_level = None;
+            }
+            GameEvent::UnknownExpedition(local) => {
+                self.current_level = None;
+                self.unknown_level = Some(local);
+            }
+            GameEvent::ExpeditionStarted
+            | GameEvent::ExpeditionSucceeded
+            | GameEvent::ExpeditionFailed
+            | GameEvent::ReturnedToLobby => {}
+        }
+    }
 }
 
 struct Table {
@@ -415,6 +779,11 @@ struct Table {
     body: scrollable::Id,
 }
 
+struct TableRow {
+    log: LogRow,
+    status: ReadStatus,
+}
+
 struct TableColumn {
     title: String,
     width: f32,
@@ -429,11 +798,3 @@ impl TableColumn {
         }
     }
 }
-
-struct Row {
-    level: String,
-    zone: String,
-    name: String,
-    id: u32,
-    read: bool,
-}
@@ -0,0 +1,222 @@
+use anyhow::{anyhow, Result};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// An expedition such as R1B1, or an extension level such as R8AX
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LevelId {
+    pub rundown: u8,
+    /// Tier letter, A being the easiest
+    pub tier: char,
+    pub index: LevelIndex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LevelIndex {
+    /// Position in the tier, starting from 1
+    Number(u8),
+    /// Extension levels like "A2 BX Extension" that are shown as AX
+    Extension,
+}
+
+impl LevelId {
+    pub fn new(rundown: u8, tier: char, index: u8) -> Self {
+        Self {
+            rundown,
+            tier,
+            index: LevelIndex::Number(index),
+        }
+    }
+
+    /// Parses a level name without the rundown, such as B1 or AX
+    pub fn from_parts(rundown: u8, level: &str) -> Result<Self> {
+        let mut chars = level.chars();
+        let tier = chars
+            .next()
+            .filter(char::is_ascii_alphabetic)
+            .ok_or_else(|| anyhow!("Invalid level '{level}'. Expected a tier such as A1 or AX"))?
+            .to_ascii_uppercase();
+        let index = match chars.as_str() {
+            "X" | "x" => LevelIndex::Extension,
+            index => match index.parse() {
+                Ok(i) if i > 0 => LevelIndex::Number(i),
+                _ => return Err(anyhow!("Invalid level '{level}'. Expected a number or X")),
+            },
+        };
+
+        Ok(Self {
+            rundown,
+            tier,
+            index,
+        })
+    }
+
+    /// The level's name within its rundown, such as B1 or AX
+    pub fn level_name(&self) -> String {
+        match self.index {
+            LevelIndex::Number(i) => format!("{}{i}", self.tier),
+            LevelIndex::Extension => format!("{}X", self.tier),
+        }
+    }
+}
+
+impl Display for LevelId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.pad(&format!("R{}{}", self.rundown, self.level_name()))
+    }
+}
+
+impl FromStr for LevelId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let rest = s
+            .strip_prefix(['R', 'r'])
+            .ok_or_else(|| anyhow!("Invalid level '{s}'. Expected a level such as R1B1"))?;
+        let split = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let rundown = rest[..split]
+            .parse()
+            .map_err(|_| anyhow!("Invalid level '{s}'. Expected a rundown number after R"))?;
+
+        LevelId::from_parts(rundown, &rest[split..])
+    }
+}
+
+/// A rundown, tier or single level that the log table can be narrowed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelScope {
+    Rundown(u8),
+    Tier { rundown: u8, tier: char },
+    Level(LevelId),
+}
+
+impl LevelScope {
+    pub fn contains(&self, level: &LevelId) -> bool {
+        match *self {
+            LevelScope::Rundown(rundown) => level.rundown == rundown,
+            LevelScope::Tier { rundown, tier } => level.rundown == rundown && level.tier == tier,
+            LevelScope::Level(id) => *level == id,
+        }
+    }
+}
+
+impl Display for LevelScope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelScope::Rundown(rundown) => write!(f, "R{rundown}"),
+            LevelScope::Tier { rundown, tier } => write!(f, "R{rundown}{tier}"),
+            LevelScope::Level(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+impl Serialize for LevelId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for LevelId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod level_id {
+        use crate::level_id::{LevelId, LevelIndex};
+
+        #[test]
+        fn parses_level() {
+            let level: LevelId = "R1B2".parse().unwrap();
+
+            assert_eq!(level, LevelId::new(1, 'B', 2));
+        }
+
+        #[test]
+        fn parses_extension_level() {
+            let level: LevelId = "r8ax".parse().unwrap();
+
+            assert_eq!(level.rundown, 8);
+            assert_eq!(level.tier, 'A');
+            assert_eq!(level.index, LevelIndex::Extension);
+            assert_eq!(level.to_string(), "R8AX");
+        }
+
+        #[test]
+        fn parses_double_digit_rundown() {
+            let level: LevelId = "R10C1".parse().unwrap();
+
+            assert_eq!(level, LevelId::new(10, 'C', 1));
+        }
+
+        #[test]
+        fn rejects_invalid_levels() {
+            for s in ["", "B1", "R", "R1", "R1B", "R1B0", "RXB1", "R11Z"] {
+                assert!(s.parse::<LevelId>().is_err(), "{s} should be invalid");
+            }
+        }
+
+        #[test]
+        fn display_round_trips() {
+            let level = LevelId::new(7, 'D', 1);
+
+            assert_eq!(level.to_string().parse::<LevelId>().unwrap(), level);
+            assert_eq!(format!("{level:<6}|"), "R7D1  |");
+        }
+
+        #[test]
+        fn sorts_by_rundown_tier_then_index() {
+            let mut levels: Vec<LevelId> = ["R2A1", "R1AX", "R1B1", "R1A2"]
+                .iter()
+                .map(|s| s.parse().unwrap())
+                .collect();
+
+            levels.sort();
+
+            let names: Vec<String> = levels.iter().map(LevelId::to_string).collect();
+            assert_eq!(names, vec!["R1A2", "R1AX", "R1B1", "R2A1"]);
+        }
+
+        #[test]
+        fn serializes_as_string() {
+            let level = LevelId::new(1, 'A', 1);
+
+            let json = serde_json::to_string(&level).unwrap();
+
+            assert_eq!(json, r#""R1A1""#);
+            assert_eq!(serde_json::from_str::<LevelId>(&json).unwrap(), level);
+        }
+    }
+    mod level_scope {
+        use crate::level_id::{LevelId, LevelScope};
+
+        #[test]
+        fn rundown_doesnt_match_longer_rundown_numbers() {
+            let scope = LevelScope::Rundown(1);
+
+            assert!(scope.contains(&LevelId::new(1, 'B', 2)));
+            assert!(!scope.contains(&LevelId::new(10, 'B', 2)));
+        }
+
+        #[test]
+        fn tier_matches_its_levels() {
+            let scope = LevelScope::Tier {
+                rundown: 7,
+                tier: 'D',
+            };
+
+            assert!(scope.contains(&"R7D1".parse().unwrap()));
+            assert!(scope.contains(&"R7DX".parse().unwrap()));
+            assert!(!scope.contains(&"R7C1".parse().unwrap()));
+            assert!(!scope.contains(&"R8D1".parse().unwrap()));
+            assert_eq!(scope.to_string(), "R7D");
+        }
+    }
+}
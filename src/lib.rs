@@ -1,148 +1,379 @@
-use crate::game_data::StoryLog;
+use crate::game_data::{Dataset, LevelMap, StoryLog};
+use crate::game_events::{EventParser, GameEvent};
+use crate::level_id::LevelId;
+use crate::play_fab::{ClientSettings, PlayFabError, SessionCache, SteamTicketProvider};
+use crate::progress::{ProgressStore, ReadRecord, ReadSource};
+use crate::snapshot::PlayFabSnapshot;
 use anyhow::{anyhow, Context, Result};
 use once_cell::sync::Lazy;
-use regex::{Match, Regex};
-use std::collections::HashSet;
+use regex::Regex;
+use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
-use time::{Date, Month, PrimitiveDateTime, Time};
-
+use std::time::Duration;
+use time::macros::format_description;
+use time::{OffsetDateTime, PrimitiveDateTime, UtcOffset};
+
+pub mod cli;
+pub mod config;
+pub mod datablocks;
+pub mod dataset_diff;
+pub mod export;
 pub mod game_data;
+pub mod game_events;
 pub mod iced_gui;
-mod play_fab;
+pub mod level_id;
+pub mod play_fab;
+pub mod progress;
+pub mod snapshot;
 #[cfg(target_os = "linux")]
-mod steam;
+pub mod steam;
+#[cfg(test)]
+pub(crate) mod test_support;
 
-#[derive(Default, Debug)]
-pub struct Options {
-    pub gtfo_path: PathBuf,
-    pub use_playfab: bool,
-}
+pub use config::{DataSource, Options, WatchMode};
 
 static FILE_NAME_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"GTFO\.(\d{4}\.\d{2}\.\d{2}\.\d{2}\.\d{2}\.\d{2})_.*\.txt").unwrap());
-pub static INGAME_READ_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"([A-Z0-9]{3,4}-[A-Z0-9]{3,6}(?:-[A-Z0-9]{3})?)").unwrap());
 
-pub static LEVEL_CHANGE_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"SelectActiveExpedition.*(Local_\d+,\d,\d)").unwrap());
+/// Offset of the local time zone that session file names are written in
+static LOCAL_OFFSET: Lazy<UtcOffset> = Lazy::new(|| {
+    UtcOffset::current_local_offset().unwrap_or_else(|e| {
+        log::warn!("Couldn't get local time zone, treating session times as UTC - {e}");
+        UtcOffset::UTC
+    })
+});
 
-pub async fn get_logs(
-    gtfo_path: PathBuf,
-    use_playfab: bool,
-) -> Result<(Vec<StoryLog>, HashSet<u32>)> {
-    let all_logs = game_data::load_logs()?;
+/// Looks up the local time zone. On Linux it can only be read while the process has a single
+/// thread, so call this before starting any.
+pub fn init_local_offset() {
+    Lazy::force(&LOCAL_OFFSET);
+}
 
-    log::info!("Total logs: {}", all_logs.len());
+/// The current time in the local time zone looked up by [`init_local_offset`]
+pub fn now_local() -> OffsetDateTime {
+    OffsetDateTime::now_utc().to_offset(*LOCAL_OFFSET)
+}
 
-    let read_log_ids = if use_playfab {
-        get_read_log_ids_from_play_fab().await.or_else(|e| {
-            log::warn!(
-                "Unable to read log data from PlayFab: {}. Falling back to parsing log files.",
-                e
-            );
-            get_read_log_ids_from_log_dir(&gtfo_path, &all_logs)
-        })
-    } else {
-        get_read_log_ids_from_log_dir(&gtfo_path, &all_logs)
-    }?;
-
-    Ok((all_logs, read_log_ids))
-}
-
-async fn get_read_log_ids_from_play_fab() -> Result<HashSet<u32>> {
-    log::debug!("Getting log ids from Play Fab");
-    log::debug!("Initializing Steam");
-    match steamworks::Client::init_app(493520) {
-        Ok((steam_client, _)) => {
-            log::debug!("Getting steam auth session ticket");
-            let (auth_ticket, ticket_bytes) = steam_client.user().authentication_session_ticket();
-            let http_client = reqwest::Client::new();
-            let user_data = match play_fab::login(&http_client, &ticket_bytes).await {
-                Ok(ticket) => play_fab::get_user_data(&http_client, ticket).await,
-                Err(e) => Err(e),
-            };
+/// The dataset and read progress the tracker starts with
+#[derive(Debug, Clone)]
+pub struct LoadedLogs {
+    pub dataset: Dataset,
+    pub progress: ProgressStore,
+    /// Why progress couldn't be fetched from PlayFab if it was the data source and log files
+    /// were read instead
+    pub play_fab_error: Option<PlayFabError>,
+    /// When the saved PlayFab snapshot that was used instead of PlayFab was fetched
+    pub play_fab_snapshot: Option<OffsetDateTime>,
+}
 
-            log::debug!("Cancelling steam auth session ticket");
-            steam_client
-                .user()
-                .cancel_authentication_ticket(auth_ticket);
+pub async fn get_logs(options: Options) -> Result<LoadedLogs> {
+    get_logs_in(options, &state_dir()?).await
+}
 
-            let ids = user_data.map(|d| d.read_logs.value)?;
-            log::info!("{} Read logs: {:?}", ids.len(), ids);
-            Ok(HashSet::from_iter(ids))
+/// Same as [`get_logs`] but keeps saved progress and PlayFab data in `state_dir`
+pub async fn get_logs_in(options: Options, state_dir: &Path) -> Result<LoadedLogs> {
+    let dataset = game_data::load_dataset(options.logs_data.as_deref())?;
+    log::info!("Using logs dataset {dataset}");
+    log::info!("Total logs: {}", dataset.logs.len());
+
+    let mut play_fab_error = None;
+    let mut play_fab_snapshot = None;
+    let baseline = match options.data_source {
+        DataSource::Logs => None,
+        DataSource::PlayFab | DataSource::Hybrid => {
+            let sessions = SessionCache::new(SessionCache::path_in(state_dir));
+            match get_read_log_ids_from_play_fab(play_fab_settings(&options), &sessions).await {
+                Ok(ids) => {
+                    let snapshot = PlayFabSnapshot::now(ids);
+                    if let Err(e) = snapshot.save(&PlayFabSnapshot::path_in(state_dir)) {
+                        log::error!("Couldn't save PlayFab snapshot - {e:?}");
+                    }
+                    Some(snapshot)
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Unable to read log data from PlayFab: {}. Falling back to parsing log files.",
+                        e
+                    );
+                    play_fab_error = Some(e);
+                    let snapshot = load_snapshot(&PlayFabSnapshot::path_in(state_dir));
+                    if let Some(snapshot) = &snapshot {
+                        log::warn!(
+                            "Using PlayFab snapshot from {} with {} read logs",
+                            snapshot.fetched_at,
+                            snapshot.read_log_ids.len()
+                        );
+                        play_fab_snapshot = Some(snapshot.fetched_at);
+                    }
+                    snapshot
+                }
+            }
         }
-        Err(e) => Err(anyhow!("Failed to init Steam - {e}")),
+    };
+
+    let read_log_files = options.data_source != DataSource::PlayFab || play_fab_error.is_some();
+    let (progress, changed) = load_progress(
+        &options,
+        &dataset,
+        baseline.as_ref(),
+        read_log_files,
+        state_dir,
+    )?;
+    if changed {
+        progress.save()?;
     }
+
+    Ok(LoadedLogs {
+        dataset,
+        progress,
+        play_fab_error,
+        play_fab_snapshot,
+    })
+}
+
+/// Loads the dataset and read progress without saving anything or contacting PlayFab. The
+/// last saved PlayFab data is used instead, along with saved progress and log files.
+pub fn read_logs(options: Options) -> Result<LoadedLogs> {
+    read_logs_in(options, &state_dir()?)
 }
 
-fn get_read_log_ids_from_log_dir(path: &Path, logs: &[StoryLog]) -> Result<HashSet<u32>> {
+/// Same as [`read_logs`] but uses saved progress and PlayFab data in `state_dir`
+pub fn read_logs_in(options: Options, state_dir: &Path) -> Result<LoadedLogs> {
+    let dataset = game_data::load_dataset(options.logs_data.as_deref())?;
+    log::info!("Using logs dataset {dataset}");
+
+    let baseline = match options.data_source {
+        DataSource::Logs => None,
+        DataSource::PlayFab | DataSource::Hybrid => {
+            load_snapshot(&PlayFabSnapshot::path_in(state_dir))
+        }
+    };
+    let (progress, _) = load_progress(&options, &dataset, baseline.as_ref(), true, state_dir)?;
+
+    Ok(LoadedLogs {
+        dataset,
+        progress,
+        play_fab_error: None,
+        play_fab_snapshot: baseline.map(|snapshot| snapshot.fetched_at),
+    })
+}
+
+/// Loads saved progress and merges in `baseline` and, if `read_log_files` is set, reads from
+/// the game's log files. Returns whether the progress changed.
+fn load_progress(
+    options: &Options,
+    dataset: &Dataset,
+    baseline: Option<&PlayFabSnapshot>,
+    read_log_files: bool,
+    state_dir: &Path,
+) -> Result<(ProgressStore, bool)> {
+    let mut progress = ProgressStore::load(&ProgressStore::path_in(state_dir))?;
+    let mut changed = baseline
+        .map(|snapshot| progress.merge(snapshot_reads(snapshot)))
+        .unwrap_or_default();
+    if read_log_files {
+        // Reads from log files know which level they were in. Saved progress is still used
+        // without them, such as after the game's logs have been deleted.
+        match get_read_logs_from_log_dir(&options.gtfo_path, &dataset.logs, &dataset.levels) {
+            Ok(reads) => changed += progress.merge(reads),
+            Err(e) => log::warn!("Couldn't read log files - {e:?}"),
+        }
+    }
+
+    let confirmed = baseline
+        .map(|snapshot| progress.confirm_play_fab(&snapshot.read_log_ids))
+        .unwrap_or_default();
+    log::info!(
+        "{changed} read logs added or updated in progress, {confirmed} confirmed by PlayFab"
+    );
+
+    Ok((progress, changed + confirmed > 0))
+}
+
+/// Directory the tracker keeps its own files in, such as its log file and saved progress
+pub fn state_dir() -> Result<PathBuf> {
+    #[cfg(target_os = "linux")]
+    let dir = dirs::state_dir();
+    #[cfg(target_os = "windows")]
+    let dir = dirs::data_local_dir();
+
+    dir.map(|d| d.join("gtfo-log-tracker"))
+        .ok_or_else(|| anyhow!("Unable to get state directory"))
+}
+
+fn play_fab_settings(options: &Options) -> ClientSettings {
+    ClientSettings {
+        base_url: options
+            .playfab_base_url
+            .clone()
+            .unwrap_or_else(play_fab::default_base_url),
+        timeout: options.playfab_timeout,
+        connect_timeout: options.playfab_connect_timeout,
+        retries: options.playfab_retries,
+        retry_delay: Duration::from_secs(1),
+    }
+}
+
+fn snapshot_reads(snapshot: &PlayFabSnapshot) -> HashMap<u32, ReadRecord> {
+    snapshot
+        .read_log_ids
+        .iter()
+        .map(|id| {
+            let record = ReadRecord {
+                first_seen: snapshot.fetched_at,
+                source: ReadSource::PlayFab,
+                level: None,
+                play_fab_confirmed: false,
+            };
+            (*id, record)
+        })
+        .collect()
+}
+
+fn load_snapshot(path: &Path) -> Option<PlayFabSnapshot> {
+    PlayFabSnapshot::load(path).unwrap_or_else(|e| {
+        log::warn!("Couldn't load PlayFab snapshot - {e:?}");
+        None
+    })
+}
+
+async fn get_read_log_ids_from_play_fab(
+    settings: ClientSettings,
+    sessions: &SessionCache,
+) -> Result<Vec<u32>, PlayFabError> {
+    let client = play_fab::Client::new(settings)?;
+    let ids = play_fab::get_read_log_ids(&client, &SteamTicketProvider, sessions).await?;
+    log::info!("{} Read logs: {:?}", ids.len(), ids);
+
+    Ok(ids)
+}
+
+fn get_read_logs_from_log_dir(
+    path: &Path,
+    logs: &[StoryLog],
+    levels: &LevelMap,
+) -> Result<HashMap<u32, ReadRecord>> {
+    let reads = get_log_file_reads(path, logs, levels)?;
+
+    Ok(reads
+        .into_iter()
+        .map(|(id, read)| {
+            log::debug!(
+                "Log {id} first read in '{}' from {:?}",
+                read.path.display(),
+                read.timestamp
+            );
+            let record = ReadRecord {
+                // Session file names are in local time. The current offset is used, so times
+                // from before a daylight saving change can be off by an hour. Reads only found
+                // in Player.log are approximated with now, so an earlier recorded time wins.
+                first_seen: read.timestamp.map_or_else(OffsetDateTime::now_utc, |t| {
+                    t.assume_offset(*LOCAL_OFFSET).to_offset(UtcOffset::UTC)
+                }),
+                source: ReadSource::LogFile,
+                level: read.level,
+                play_fab_confirmed: false,
+            };
+            (id, record)
+        })
+        .collect())
+}
+
+/// Where a log id was first seen as read when parsing the game's log files
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFileRead {
+    pub path: PathBuf,
+    /// When the session started, from the file name. Player.log and Player-prev.log don't
+    /// record a start time.
+    pub timestamp: Option<PrimitiveDateTime>,
+    /// Expedition that was selected when the log was read, if known
+    pub level: Option<LevelId>,
+}
+
+/// Parses every session file plus Player.log and Player-prev.log, keeping the oldest
+/// file each read log id was seen in. The Player logs are treated as the newest files.
+pub fn get_log_file_reads(
+    path: &Path,
+    logs: &[StoryLog],
+    levels: &LevelMap,
+) -> Result<HashMap<u32, LogFileRead>> {
     log::debug!("Getting log ids from local user data folder");
-    let log_path = fs::read_dir(path)
+    let mut log_files: Vec<LogFileRead> = fs::read_dir(path)
         .with_context(|| format!("Couldn't read directory '{}'", path.display()))?
         .filter_map(Result::ok)
         .filter_map(|e| {
             let path = e.path();
-            parse_file_name(&path).map(|d| (d, path))
-        })
-        .max_by(|(date1, _), (date2, _)| date1.cmp(date2))
-        .map(|(_, path)| path)
-        .or_else(|| {
-            let player_path = path.join("Player.log");
-            player_path.exists().then_some(player_path)
+            parse_file_name(&path).map(|timestamp| LogFileRead {
+                path,
+                timestamp: Some(timestamp),
+                level: None,
+            })
         })
-        .ok_or_else(|| {
-            anyhow!(
-                "Couldn't find any CLIENT/MASTER.txt files or Player.log in '{}'",
-                path.display()
-            )
-        })?;
-    let log_file = File::open(&log_path)
-        .with_context(|| format!("Couldn't open file '{}'", log_path.display()))?;
-    let lines = BufReader::new(log_file).lines().map_while(Result::ok);
-    let read_ids = parse_read_ids(lines, logs);
-
-    log::info!("{} Read logs: {:?}", read_ids.len(), read_ids);
+        .chain(["Player-prev.log", "Player.log"].iter().filter_map(|name| {
+            let path = path.join(name);
+            path.is_file().then_some(LogFileRead {
+                path,
+                timestamp: None,
+                level: None,
+            })
+        }))
+        .collect();
+
+    if log_files.is_empty() {
+        return Err(anyhow!(
+            "Couldn't find any CLIENT/MASTER.txt files or Player.log in '{}'",
+            path.display()
+        ));
+    }
 
-    Ok(HashSet::from_iter(read_ids))
-}
+    // The sort is stable so Player-prev.log stays before Player.log
+    log_files.sort_by_key(|f| (f.timestamp.is_none(), f.timestamp));
+
+    let mut reads = HashMap::new();
+    for log_file in log_files {
+        let file = match File::open(&log_file.path) {
+            Ok(file) => file,
+            Err(e) => {
+                log::warn!("Couldn't open file '{}' - {e}", log_file.path.display());
+                continue;
+            }
+        };
+        let lines = BufReader::new(file).lines().map_while(Result::ok);
+
+        for (id, level) in parse_read_ids(lines, logs, levels) {
+            reads.entry(id).or_insert_with(|| LogFileRead {
+                level,
+                ..log_file.clone()
+            });
+        }
+    }
 
-pub fn try_get_log_id(m: &Match, all_logs: &[StoryLog]) -> Option<u32> {
-    let mut name = String::new();
-    m.as_str().clone_into(&mut name);
-    game_data::get_id_from_name(&name, all_logs)
-}
+    log::info!("{} Read logs: {:?}", reads.len(), reads.keys());
 
-pub fn try_get_new_level(m: &Match) -> Option<String> {
-    let mut name = String::new();
-    m.as_str().clone_into(&mut name);
-    game_data::get_level_from_local(&name)
+    Ok(reads)
 }
 
-fn parse_read_ids(lines: impl Iterator<Item = String>, logs: &[StoryLog]) -> Vec<u32> {
-    static PREVIOUSLY_READ_REGEX: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"Logs Read: \d+ / \d+ \| IDs: \[(\d+(?:,\s*\d+)*)]\s*$").unwrap());
+/// Returns each read log id along with the level that was selected when it was read
+fn parse_read_ids(
+    lines: impl Iterator<Item = String>,
+    logs: &[StoryLog],
+    levels: &LevelMap,
+) -> Vec<(u32, Option<LevelId>)> {
+    let mut parser = EventParser::new(logs, levels);
     let mut read_ids = Vec::new();
 
     for line in lines {
-        if let Some(m) = PREVIOUSLY_READ_REGEX
-            .captures(line.as_str())
-            .and_then(|c| c.get(1))
-        {
-            let ids = m
-                .as_str()
-                .split(',')
-                .filter_map(|id| id.trim().parse::<u32>().ok());
-            read_ids.extend(ids);
-        }
-
-        if let Some(id) = INGAME_READ_REGEX
-            .find(line.as_str())
-            .and_then(|m| try_get_log_id(&m, logs))
-        {
-            read_ids.push(id);
+        for timed in parser.parse_line(&line) {
+            match timed.event {
+                GameEvent::ReadSummary(ids) => {
+                    read_ids.extend(ids.into_iter().map(|id| (id, None)))
+                }
+                GameEvent::LogRead(id) => read_ids.push((id, parser.level())),
+                _ => {}
+            }
         }
     }
 
@@ -153,21 +384,18 @@ fn file_contains_log_ids(file_name: &str) -> bool {
     FILE_NAME_REGEX.is_match(file_name)
 }
 
+/// Session start time from a session file name. Returns `None` for other files and for
+/// session file names with an impossible date or time, which are skipped with a warning.
 fn parse_file_name(path: &Path) -> Option<PrimitiveDateTime> {
-    let file_name = path.file_name()?.to_str().unwrap_or_default();
-
-    FILE_NAME_REGEX.captures(file_name)?.get(1).map(|m| {
-        let parts: Vec<&str> = m.as_str().split('.').collect();
-        let year = parts[0].parse::<i32>().unwrap();
-        let nums: Vec<u8> = parts[1..]
-            .iter()
-            .map(|s| s.parse::<u8>().unwrap())
-            .collect();
-        let date =
-            Date::from_calendar_date(year, Month::try_from(nums[0]).unwrap(), nums[1]).unwrap();
-        let time = Time::from_hms(nums[2], nums[3], nums[4]).unwrap();
-        PrimitiveDateTime::new(date, time)
-    })
+    let file_name = path.file_name()?.to_str()?;
+    let timestamp = FILE_NAME_REGEX.captures(file_name)?.get(1)?.as_str();
+
+    PrimitiveDateTime::parse(
+        timestamp,
+        format_description!("[year].[month].[day].[hour].[minute].[second]"),
+    )
+    .map_err(|e| log::warn!("Skipping session file '{file_name}' with invalid date: {e}"))
+    .ok()
 }
 
 #[cfg(test)]
@@ -196,6 +424,227 @@ mod tests {
             assert!(result)
         }
     }
+    mod get_log_file_reads {
+        use crate::game_data::LevelMap;
+        use crate::get_log_file_reads;
+        use crate::test_support::{story_log, test_dir};
+        use std::fs;
+
+        #[test]
+        fn unions_ids_from_all_files() {
+            let dir = test_dir("unions-ids");
+            let logs = vec![
+                story_log(1, "AAA-BBB-CCC", &["R1A1"]),
+                story_log(2, "DDD-EEE-FFF", &["R1A1"]),
+            ];
+            fs::write(
+                dir.join("GTFO.2023.12.22.00.25.30_NoName_CLIENT.txt"),
+                "Logs Read: 1 / 3 | IDs: [3]\nAAA-BBB-CCC",
+            )
+            .unwrap();
+            fs::write(
+                dir.join("GTFO.2023.12.23.00.25.30_NoName_CLIENT.txt"),
+                "DDD-EEE-FFF",
+            )
+            .unwrap();
+            fs::write(dir.join("Player.log"), "AAA-BBB-CCC").unwrap();
+
+            let reads = get_log_file_reads(&dir, &logs, &LevelMap::built_in()).unwrap();
+
+            let mut ids: Vec<u32> = reads.keys().copied().collect();
+            ids.sort();
+            assert_eq!(ids, vec![1, 2, 3]);
+        }
+
+        #[test]
+        fn keeps_oldest_file_for_id() {
+            let dir = test_dir("oldest-file");
+            let logs = vec![story_log(1, "AAA-BBB-CCC", &["R1A1"])];
+            let old_file = dir.join("GTFO.2023.12.22.00.25.30_NoName_CLIENT.txt");
+            fs::write(&old_file, "AAA-BBB-CCC").unwrap();
+            fs::write(
+                dir.join("GTFO.2023.12.23.00.25.30_NoName_CLIENT.txt"),
+                "AAA-BBB-CCC",
+            )
+            .unwrap();
+
+            let reads = get_log_file_reads(&dir, &logs, &LevelMap::built_in()).unwrap();
+
+            assert_eq!(reads[&1].path, old_file);
+            assert_eq!(reads[&1].timestamp.unwrap().day(), 22);
+        }
+
+        #[test]
+        fn skips_session_file_with_invalid_date() {
+            let dir = test_dir("invalid-date");
+            let logs = vec![
+                story_log(1, "AAA-BBB-CCC", &["R1A1"]),
+                story_log(2, "DDD-EEE-FFF", &["R1A1"]),
+            ];
+            fs::write(
+                dir.join("GTFO.2023.13.45.00.25.30_NoName_CLIENT.txt"),
+                "AAA-BBB-CCC",
+            )
+            .unwrap();
+            fs::write(
+                dir.join("GTFO.2023.12.23.00.25.30_NoName_CLIENT.txt"),
+                "DDD-EEE-FFF",
+            )
+            .unwrap();
+
+            let reads = get_log_file_reads(&dir, &logs, &LevelMap::built_in()).unwrap();
+
+            assert_eq!(reads.keys().copied().collect::<Vec<_>>(), vec![2]);
+        }
+
+        #[test]
+        fn prefers_session_files_over_player_log() {
+            let dir = test_dir("player-log");
+            let logs = vec![
+                story_log(1, "AAA-BBB-CCC", &["R1A1"]),
+                story_log(2, "DDD-EEE-FFF", &["R1A1"]),
+            ];
+            let session_file = dir.join("GTFO.2023.12.22.00.25.30_NoName_CLIENT.txt");
+            fs::write(&session_file, "AAA-BBB-CCC").unwrap();
+            fs::write(dir.join("Player.log"), "AAA-BBB-CCC\nDDD-EEE-FFF").unwrap();
+
+            let reads = get_log_file_reads(&dir, &logs, &LevelMap::built_in()).unwrap();
+
+            assert_eq!(reads[&1].path, session_file);
+            assert_eq!(reads[&2].path, dir.join("Player.log"));
+            assert_eq!(reads[&2].timestamp, None);
+        }
+
+        #[test]
+        fn fails_if_no_log_files() {
+            let dir = test_dir("no-files");
+
+            let result = get_log_file_reads(&dir, &[], &LevelMap::built_in());
+
+            assert!(result.is_err());
+        }
+    }
+    mod get_logs_in {
+        use crate::play_fab::SessionCache;
+        use crate::snapshot::PlayFabSnapshot;
+        use crate::test_support::{serve_once, test_dir};
+        use crate::{get_logs_in, read_logs_in, DataSource, Options};
+        use std::fs;
+        use std::path::Path;
+        use std::time::Duration;
+        use time::macros::datetime;
+
+        /// A log in the built-in dataset with a name no other log shares
+        const LOG_ID: u32 = 2159521601;
+        const LOG_NAME: &str = "834-786-872";
+
+        #[tokio::test]
+        async fn keeps_progress_after_logs_are_deleted() {
+            let state_dir = test_dir("state-deleted-logs");
+            let gtfo_dir = test_dir("gtfo-deleted-logs");
+            fs::write(
+                gtfo_dir.join("GTFO.2023.12.22.00.25.30_NoName_CLIENT.txt"),
+                LOG_NAME,
+            )
+            .unwrap();
+            let options = Options {
+                gtfo_path: gtfo_dir.to_path_buf(),
+                ..Options::default()
+            };
+
+            get_logs_in(options.clone(), &state_dir).await.unwrap();
+            fs::remove_dir_all(&*gtfo_dir).unwrap();
+            let loaded = get_logs_in(options, &state_dir).await.unwrap();
+
+            assert!(loaded.progress.read_ids().contains(&LOG_ID));
+        }
+
+        /// Options for the PlayFab at `base_url` without game logs. A saved session is used so
+        /// Steam isn't needed.
+        fn play_fab_at(base_url: &str, state_dir: &Path, data_source: DataSource) -> Options {
+            fs::write(
+                SessionCache::path_in(state_dir),
+                r#"{"ticket": "session", "expiresAt": "2999-01-01T00:00:00Z"}"#,
+            )
+            .unwrap();
+
+            Options {
+                gtfo_path: state_dir.join("no-logs"),
+                data_source,
+                playfab_connect_timeout: Duration::from_secs(1),
+                playfab_retries: 0,
+                playfab_base_url: Some(base_url.to_string()),
+                ..Options::default()
+            }
+        }
+
+        #[tokio::test]
+        async fn uses_snapshot_without_play_fab_or_logs() {
+            let state_dir = test_dir("state-snapshot-only");
+            let snapshot = PlayFabSnapshot {
+                fetched_at: datetime!(2024-02-14 12:00 UTC),
+                read_log_ids: vec![LOG_ID],
+            };
+            snapshot
+                .save(&PlayFabSnapshot::path_in(&state_dir))
+                .unwrap();
+            // Nothing listens on port 1, so the connection is refused
+            let options = play_fab_at("http://127.0.0.1:1", &state_dir, DataSource::PlayFab);
+
+            let loaded = get_logs_in(options, &state_dir).await.unwrap();
+
+            assert!(loaded.play_fab_error.is_some());
+            assert_eq!(loaded.play_fab_snapshot, Some(snapshot.fetched_at));
+            assert!(loaded.progress.read_ids().contains(&LOG_ID));
+        }
+
+        #[test]
+        fn read_only_uses_saved_data_without_saving() {
+            let state_dir = test_dir("state-read-only");
+            let gtfo_dir = test_dir("gtfo-read-only");
+            PlayFabSnapshot {
+                fetched_at: datetime!(2024-02-14 12:00 UTC),
+                read_log_ids: vec![1],
+            }
+            .save(&PlayFabSnapshot::path_in(&state_dir))
+            .unwrap();
+            fs::write(
+                gtfo_dir.join("GTFO.2023.12.22.00.25.30_NoName_CLIENT.txt"),
+                LOG_NAME,
+            )
+            .unwrap();
+            let options = Options {
+                gtfo_path: gtfo_dir.to_path_buf(),
+                data_source: DataSource::Hybrid,
+                ..Options::default()
+            };
+
+            let loaded = read_logs_in(options, &state_dir).unwrap();
+
+            assert!(loaded.progress.read_ids().contains(&1));
+            assert!(loaded.progress.read_ids().contains(&LOG_ID));
+            assert_eq!(
+                fs::read_dir(&*state_dir).unwrap().count(),
+                1,
+                "only the snapshot should be in the state directory"
+            );
+        }
+
+        #[tokio::test]
+        async fn hybrid_uses_play_fab_without_logs() {
+            let state_dir = test_dir("state-hybrid-no-logs");
+            let base_url = serve_once(
+                r#"{"code": 200, "status": "OK", "data": {"Data": {"readlogs": {"Value": "[2159521601]"}}}}"#,
+            );
+            let options = play_fab_at(&base_url, &state_dir, DataSource::Hybrid);
+
+            let loaded = get_logs_in(options, &state_dir).await.unwrap();
+
+            assert!(loaded.play_fab_error.is_none());
+            let record = loaded.progress.get(LOG_ID).unwrap();
+            assert!(record.confirmed_by_play_fab());
+        }
+    }
     mod parse_file_name {
         use crate::parse_file_name;
         use std::path::PathBuf;
@@ -214,5 +663,19 @@ mod tests {
             assert_eq!(date.minute(), 25);
             assert_eq!(date.second(), 30);
         }
+
+        #[test]
+        fn skips_invalid_date() {
+            let file_name = PathBuf::from("GTFO.2023.13.22.00.25.30_NoName_CLIENT.txt");
+
+            assert_eq!(parse_file_name(&file_name), None);
+        }
+
+        #[test]
+        fn skips_other_files() {
+            let file_name = PathBuf::from("Player.log");
+
+            assert_eq!(parse_file_name(&file_name), None);
+        }
     }
 }
@@ -1,24 +1,45 @@
 #![windows_subsystem = "windows"]
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{Context, Result};
 use gtfo_log_tracker::iced_gui::GtfoLogTracker;
-use gtfo_log_tracker::{game_data, Options};
+use gtfo_log_tracker::{cli, config, game_data, init_local_offset, state_dir, Options};
 use iced::Application;
 use simplelog::{
     ColorChoice, CombinedLogger, ConfigBuilder, LevelFilter, TermLogger, TerminalMode, WriteLogger,
 };
 use std::fs::File;
-use std::path::PathBuf;
 use std::{env, fs};
 
 fn main() -> Result<()> {
-    init_logger()?;
-
     let args: Vec<String> = env::args().collect();
+    let command = cli::parse_command(&args)?;
+
+    if command.is_some() {
+        attach_console();
+    }
+    // Keep the terminal clean for command output
+    init_logger(if command.is_some() {
+        LevelFilter::Warn
+    } else {
+        LevelFilter::Debug
+    })?;
+    // Before anything starts other threads
+    init_local_offset();
     log_runtime_info(&args);
 
-    let options = get_options(args)?;
-    log::debug!("{options:?}");
+    let command = match command {
+        Some(command) if !command.uses_progress() => return cli::run(command, Options::default()),
+        command => command,
+    };
+
+    let options = config::load_options(&args, || {
+        game_data::find_user_data_path().with_context(|| "Couldn't get GTFO user data path")
+    })?;
+    log::info!("Resolved configuration: {options:#?}");
+
+    if let Some(command) = command {
+        return cli::run(command, options);
+    }
 
     #[cfg(target_os = "linux")]
     env::set_var("MANGOHUD", "0");
@@ -27,34 +48,20 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn get_options(args: Vec<String>) -> Result<Options> {
-    Ok(Options {
-        gtfo_path: args
-            .iter()
-            .position(|s| s == "--data-path")
-            .and_then(|i| args.get(i + 1))
-            .map(PathBuf::from)
-            .ok_or_else(|| anyhow!(""))
-            .or_else(|_| game_data::find_user_data_path())
-            .with_context(|| "Couldn't get GTFO user data path")?,
-        use_playfab: args
-            .iter()
-            .find(|s| *s == "--playfab")
-            .map(|_| true)
-            .unwrap_or(false),
-    })
+/// Release builds use the windows subsystem which doesn't get a console, so borrow the one
+/// the tracker was launched from
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    let _ = unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
 }
 
-fn init_logger() -> Result<()> {
-    #[cfg(target_os = "linux")]
-    let log_dir = dirs::state_dir();
-    #[cfg(target_os = "windows")]
-    let log_dir = dirs::data_local_dir();
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
 
-    let log_path = log_dir
-        .ok_or_else(|| anyhow!("Unable to get log directory"))?
-        .join("gtfo-log-tracker")
-        .join("log.txt");
+fn init_logger(term_level: LevelFilter) -> Result<()> {
+    let log_path = state_dir()?.join("log.txt");
 
     fs::create_dir_all(log_path.parent().expect("Log path must include file name"))
         .with_context(|| format!("Couldn't create log dir for path '{}'", log_path.display()))?;
@@ -71,7 +78,7 @@ fn init_logger() -> Result<()> {
 
     CombinedLogger::init(vec![
         TermLogger::new(
-            LevelFilter::Debug,
+            term_level,
             term_config,
             TerminalMode::Mixed,
             ColorChoice::Auto,
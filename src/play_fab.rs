@@ -1,6 +1,13 @@
-use anyhow::{anyhow, Result};
+use anyhow::Context;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Write};
+use std::fs;
+use std::future::Future;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use time::OffsetDateTime;
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase", untagged)]
@@ -9,29 +16,143 @@ enum ApiResponse<T> {
     Error(ErrorDetails),
 }
 
-#[derive(Deserialize, Debug)]
+/// The error body PlayFab sends with a failed request
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
-struct ErrorDetails {
-    code: u16,
-    error: String,
-    error_code: String,
-    error_details: String,
-    error_message: String,
-    status: String,
+pub struct ErrorDetails {
+    /// HTTP status code
+    pub code: u16,
+    pub status: String,
+    /// Name of the PlayFab error, such as `InvalidSteamTicket`
+    pub error: String,
+    pub error_code: u32,
+    pub error_message: String,
+    #[serde(default)]
+    pub error_details: Option<HashMap<String, Vec<String>>>,
+    /// Sent when a request is throttled
+    #[serde(default)]
+    pub retry_after_seconds: Option<u64>,
 }
 
 impl Display for ErrorDetails {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "HTTP: {} - {} {{ error: {}, error_code: {}, error_details: {}, error_message: {} }}",
-            self.code,
-            self.status,
-            self.error,
-            self.error_code,
-            self.error_details,
-            self.error_message
-        )
+            "HTTP: {} - {} {{ error: {}, error_code: {}, error_message: {}",
+            self.code, self.status, self.error, self.error_code, self.error_message
+        )?;
+        if let Some(details) = &self.error_details {
+            write!(f, ", error_details: {details:?}")?;
+        }
+        write!(f, " }}")
+    }
+}
+
+/// PlayFab errors that go away if the request is tried again later
+const TRANSIENT_ERRORS: [&str; 6] = [
+    "APIClientRequestRateLimitExceeded",
+    "APIConcurrentRequestLimitExceeded",
+    "DownstreamServiceUnavailable",
+    "InternalServerError",
+    "Overloaded",
+    "ServiceUnavailable",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlayFabError {
+    /// Steam needs to be running and logged in to get a ticket to log into PlayFab with
+    Steam(String),
+    /// No response within the configured timeout
+    Timeout,
+    /// Couldn't connect or the connection was dropped
+    Network(String),
+    /// PlayFab rejected the request
+    Api(Box<ErrorDetails>),
+    /// The response wasn't one PlayFab normally sends, such as a proxy's error page
+    InvalidResponse { status: u16, message: String },
+}
+
+impl PlayFabError {
+    /// Whether trying again later could succeed
+    pub fn is_transient(&self) -> bool {
+        let transient_status = |status: u16| status == 429 || status >= 500;
+        match self {
+            PlayFabError::Steam(_) => false,
+            PlayFabError::Timeout | PlayFabError::Network(_) => true,
+            PlayFabError::Api(details) => {
+                transient_status(details.code) || TRANSIENT_ERRORS.contains(&details.error.as_str())
+            }
+            PlayFabError::InvalidResponse { status, .. } => transient_status(*status),
+        }
+    }
+
+    /// Whether the session ticket was rejected, so logging in again could succeed
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self, PlayFabError::Api(details) if details.code == 401)
+    }
+
+    fn from_reqwest(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            PlayFabError::Timeout
+        } else if e.is_decode() {
+            PlayFabError::InvalidResponse {
+                status: e.status().map(|s| s.as_u16()).unwrap_or_default(),
+                message: e.to_string(),
+            }
+        } else {
+            PlayFabError::Network(e.to_string())
+        }
+    }
+}
+
+impl Display for PlayFabError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlayFabError::Steam(e) => write!(f, "Failed to init Steam - {e}"),
+            PlayFabError::Timeout => write!(f, "PlayFab didn't respond in time"),
+            PlayFabError::Network(e) => write!(f, "Couldn't reach PlayFab - {e}"),
+            PlayFabError::Api(details) => write!(f, "PlayFab returned an error - {details}"),
+            PlayFabError::InvalidResponse { status, message } => {
+                write!(
+                    f,
+                    "Unexpected response from PlayFab (HTTP {status}) - {message}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlayFabError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientSettings {
+    /// Where the PlayFab API is, normally [default_base_url]
+    pub base_url: String,
+    /// Limit for a whole request, including reading the response
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    /// How many times a request is tried again after a transient error
+    pub retries: u32,
+    /// Delay before the first retry. It doubles for each retry after that.
+    pub retry_delay: Duration,
+}
+
+/// Longest time to wait between retries, even if PlayFab asks for longer
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+impl ClientSettings {
+    fn delay_before_retry(&self, attempt: u32, error: &PlayFabError) -> Duration {
+        let requested = match error {
+            PlayFabError::Api(details) => details.retry_after_seconds.map(Duration::from_secs),
+            _ => None,
+        };
+
+        requested
+            .unwrap_or_else(|| {
+                self.retry_delay
+                    .saturating_mul(2u32.saturating_pow(attempt))
+            })
+            .min(MAX_RETRY_DELAY)
     }
 }
 
@@ -80,51 +201,311 @@ pub struct ReadLogs {
     pub value: Vec<u32>,
 }
 
-pub struct SessionTicket(String);
+/// Sent with every request after logging in
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTicket {
+    ticket: String,
+    #[serde(with = "time::serde::rfc3339")]
+    expires_at: OffsetDateTime,
+}
+
+/// How long a session ticket is reused for. PlayFab doesn't say when a session ticket expires,
+/// only the entity token that comes with it, and documents tickets as lasting 24 hours. This is
+/// kept well under that, but it's only a hint: a ticket PlayFab rejects early gets a new login.
+const SESSION_LIFETIME: time::Duration = time::Duration::hours(12);
+/// Session tickets that expire sooner than this are treated as expired so they don't run out
+/// part way through a refresh
+const SESSION_EXPIRY_MARGIN: time::Duration = time::Duration::minutes(5);
+
+impl SessionTicket {
+    pub fn is_valid_at(&self, time: OffsetDateTime) -> bool {
+        time + SESSION_EXPIRY_MARGIN < self.expires_at
+    }
+}
+
+impl std::fmt::Debug for SessionTicket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionTicket")
+            .field("expires_at", &self.expires_at)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The last session ticket, saved so later runs don't have to log in, and so initialize
+/// Steam, again. Only the current user can read the file.
+pub struct SessionCache {
+    path: PathBuf,
+}
+
+impl SessionCache {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path_in(state_dir: &Path) -> PathBuf {
+        state_dir.join("playfab-session.json")
+    }
+
+    /// Returns the saved ticket if there is one that hasn't expired
+    pub fn load(&self) -> Option<SessionTicket> {
+        if !self.path.exists() {
+            return None;
+        }
+
+        let ticket = fs::read_to_string(&self.path)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| serde_json::from_str::<SessionTicket>(&json).map_err(Into::into));
+        match ticket {
+            Ok(ticket) if ticket.is_valid_at(OffsetDateTime::now_utc()) => Some(ticket),
+            Ok(_) => {
+                log::debug!("Saved PlayFab session has expired");
+                None
+            }
+            Err(e) => {
+                log::warn!(
+                    "Couldn't load PlayFab session from '{}' - {e}",
+                    self.path.display()
+                );
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, ticket: &SessionTicket) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Couldn't create directory '{}'", dir.display()))?;
+        }
+        let json = serde_json::to_string(ticket)?;
+        write_private(&self.path, json.as_bytes())
+            .with_context(|| format!("Couldn't write PlayFab session '{}'", self.path.display()))
+    }
+
+    pub fn clear(&self) {
+        if self.path.exists() {
+            if let Err(e) = fs::remove_file(&self.path) {
+                log::warn!(
+                    "Couldn't remove PlayFab session '{}' - {e}",
+                    self.path.display()
+                );
+            }
+        }
+    }
+}
+
+/// Writes `contents` to a file only the current user can read. On Windows, files in the
+/// user's local app data are already private.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to new files
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+
+    options.open(path)?.write_all(contents)
+}
 
 const GTFO_TITLE_ID: &str = "8f9";
+const GTFO_APP_ID: u32 = 493520;
+
+pub fn default_base_url() -> String {
+    format!("https://{GTFO_TITLE_ID}.playfabapi.com")
+}
+
+/// Gets the Steam auth session tickets PlayFab logs in with
+pub trait TicketProvider {
+    /// Dropping the ticket cancels it
+    type Ticket: AsRef<[u8]>;
+
+    fn auth_ticket(&self) -> Result<Self::Ticket, PlayFabError>;
+}
+
+/// Gets tickets from the running Steam client. Steam is only initialized when a ticket is
+/// needed.
+pub struct SteamTicketProvider;
+
+pub struct SteamTicket {
+    client: steamworks::Client,
+    handle: steamworks::AuthTicket,
+    bytes: Vec<u8>,
+}
+
+impl TicketProvider for SteamTicketProvider {
+    type Ticket = SteamTicket;
+
+    fn auth_ticket(&self) -> Result<SteamTicket, PlayFabError> {
+        log::debug!("Initializing Steam");
+        let (client, _) = steamworks::Client::init_app(GTFO_APP_ID)
+            .map_err(|e| PlayFabError::Steam(e.to_string()))?;
+        log::debug!("Getting steam auth session ticket");
+        let (handle, bytes) = client.user().authentication_session_ticket();
+
+        Ok(SteamTicket {
+            client,
+            handle,
+            bytes,
+        })
+    }
+}
 
-pub async fn login(http_client: &reqwest::Client, steam_ticket: &[u8]) -> Result<SessionTicket> {
-    let req_body = LoginRequest::new(GTFO_TITLE_ID, to_hex(steam_ticket));
-
-    log::debug!("Logging into Play Fab vis Steam");
-    let res = http_client
-        .post(format!(
-            "https://{}.playfabapi.com/Client/LoginWithSteam",
-            GTFO_TITLE_ID
-        ))
-        .json(&req_body)
-        .send()
-        .await?
-        .json::<ApiResponse<LoginResponse>>()
+impl AsRef<[u8]> for SteamTicket {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Drop for SteamTicket {
+    fn drop(&mut self) {
+        log::debug!("Cancelling steam auth session ticket");
+        self.client.user().cancel_authentication_ticket(self.handle);
+    }
+}
+
+/// Gets the ids of every read log. The session saved in `sessions` is used if it's still
+/// valid, otherwise this logs into PlayFab with a ticket from `tickets` and saves the new
+/// session.
+pub async fn get_read_log_ids<P: TicketProvider>(
+    client: &Client,
+    tickets: &P,
+    sessions: &SessionCache,
+) -> Result<Vec<u32>, PlayFabError> {
+    log::debug!("Getting log ids from Play Fab");
+    if let Some(session_ticket) = sessions.load() {
+        log::debug!("Using saved PlayFab session");
+        match client.get_user_data(&session_ticket).await {
+            Ok(user_data) => return Ok(user_data.read_logs.value),
+            Err(e) if e.is_unauthorized() => {
+                log::info!("Saved PlayFab session was rejected, logging in again - {e}");
+                sessions.clear();
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let steam_ticket = tickets.auth_ticket()?;
+    let session_ticket = client.login(steam_ticket.as_ref()).await?;
+    if let Err(e) = sessions.save(&session_ticket) {
+        log::warn!("Couldn't save PlayFab session - {e:?}");
+    }
+    let user_data = client.get_user_data(&session_ticket).await?;
+
+    Ok(user_data.read_logs.value)
+}
+
+pub struct Client {
+    http_client: reqwest::Client,
+    settings: ClientSettings,
+}
+
+impl Client {
+    pub fn new(settings: ClientSettings) -> Result<Self, PlayFabError> {
+        let http_client = reqwest::Client::builder()
+            .timeout(settings.timeout)
+            .connect_timeout(settings.connect_timeout)
+            .build()
+            .map_err(PlayFabError::from_reqwest)?;
+
+        Ok(Client {
+            http_client,
+            settings,
+        })
+    }
+
+    pub async fn login(&self, steam_ticket: &[u8]) -> Result<SessionTicket, PlayFabError> {
+        let req_body = LoginRequest::new(GTFO_TITLE_ID, to_hex(steam_ticket));
+
+        log::debug!("Logging into Play Fab vis Steam");
+        let data: LoginResponse = retry(&self.settings, || {
+            self.post("LoginWithSteam", None, Some(&req_body))
+        })
         .await?;
 
-    match res {
-        ApiResponse::Success { data } => Ok(SessionTicket(data.session_ticket)),
-        ApiResponse::Error(e) => Err(anyhow!("{e}")),
-    }
-}
-
-pub async fn get_user_data(
-    http_client: &reqwest::Client,
-    session_ticket: SessionTicket,
-) -> Result<UserData> {
-    log::debug!("Getting user data from Play Fab");
-    let res = http_client
-        .post(format!(
-            "https://{}.playfabapi.com/Client/GetUserData",
-            GTFO_TITLE_ID
-        ))
-        .header("X-Authorization", session_ticket.0)
-        .header("Content-Type", "application/json")
-        .send()
-        .await?
-        .json::<ApiResponse<UserDataResponse>>()
+        Ok(SessionTicket {
+            ticket: data.session_ticket,
+            expires_at: OffsetDateTime::now_utc() + SESSION_LIFETIME,
+        })
+    }
+
+    pub async fn get_user_data(
+        &self,
+        session_ticket: &SessionTicket,
+    ) -> Result<UserData, PlayFabError> {
+        log::debug!("Getting user data from Play Fab");
+        let data: UserDataResponse = retry(&self.settings, || {
+            self.post::<_, ()>("GetUserData", Some(session_ticket), None)
+        })
         .await?;
 
-    match res {
-        ApiResponse::Success { data } => Ok(data.data),
-        ApiResponse::Error(e) => Err(anyhow!("{e}")),
+        Ok(data.data)
+    }
+
+    async fn post<T, B>(
+        &self,
+        api: &str,
+        session_ticket: Option<&SessionTicket>,
+        body: Option<&B>,
+    ) -> Result<T, PlayFabError>
+    where
+        T: for<'de> Deserialize<'de>,
+        B: Serialize,
+    {
+        let mut request = self
+            .http_client
+            .post(format!(
+                "{}/Client/{api}",
+                self.settings.base_url.trim_end_matches('/')
+            ))
+            .header("Content-Type", "application/json");
+        if let Some(ticket) = session_ticket {
+            request = request.header("X-Authorization", &ticket.ticket);
+        }
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request.send().await.map_err(PlayFabError::from_reqwest)?;
+        let status = response.status().as_u16();
+        let text = response.text().await.map_err(PlayFabError::from_reqwest)?;
+
+        match serde_json::from_str(&text) {
+            Ok(ApiResponse::Success { data }) => Ok(data),
+            Ok(ApiResponse::Error(details)) => Err(PlayFabError::Api(Box::new(details))),
+            Err(e) => Err(PlayFabError::InvalidResponse {
+                status,
+                message: e.to_string(),
+            }),
+        }
+    }
+}
+
+/// Runs `request` until it succeeds, fails with an error that isn't transient or runs out of
+/// retries
+async fn retry<T, F, Fut>(settings: &ClientSettings, mut request: F) -> Result<T, PlayFabError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, PlayFabError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Err(e) if e.is_transient() && attempt < settings.retries => {
+                let delay = settings.delay_before_retry(attempt, &e);
+                attempt += 1;
+                log::warn!(
+                    "PlayFab request failed, retrying in {delay:?} ({attempt}/{}) - {e}",
+                    settings.retries
+                );
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
     }
 }
 
@@ -158,6 +539,172 @@ fn to_hex(bytes: &[u8]) -> String {
 
 #[cfg(test)]
 mod tests {
+    mod play_fab_error {
+        use crate::play_fab::{ApiResponse, ErrorDetails, PlayFabError};
+
+        const THROTTLED: &str = r#"{
+            "code": 429,
+            "status": "TooManyRequests",
+            "error": "APIClientRequestRateLimitExceeded",
+            "errorCode": 1199,
+            "errorMessage": "The client has exceeded the maximum API request rate",
+            "retryAfterSeconds": 3
+        }"#;
+
+        fn details(json: &str) -> ErrorDetails {
+            match serde_json::from_str::<ApiResponse<()>>(json).unwrap() {
+                ApiResponse::Error(details) => details,
+                ApiResponse::Success { .. } => panic!("Expected an error"),
+            }
+        }
+
+        #[test]
+        fn parses_error_response() {
+            let details = details(
+                r#"{
+                    "code": 400,
+                    "status": "BadRequest",
+                    "error": "InvalidSteamTicket",
+                    "errorCode": 1010,
+                    "errorMessage": "Steam API AuthenticateUserTicket error response",
+                    "errorDetails": { "SteamTicket": ["Invalid ticket"] }
+                }"#,
+            );
+
+            assert_eq!(details.error, "InvalidSteamTicket");
+            assert_eq!(details.error_code, 1010);
+            assert_eq!(
+                details.error_details.unwrap()["SteamTicket"],
+                vec!["Invalid ticket"]
+            );
+        }
+
+        #[test]
+        fn throttling_is_transient() {
+            let details = details(THROTTLED);
+
+            assert_eq!(details.retry_after_seconds, Some(3));
+            assert!(PlayFabError::Api(Box::new(details)).is_transient());
+        }
+
+        #[test]
+        fn rejected_ticket_is_not_transient() {
+            let mut details = details(THROTTLED);
+            details.code = 400;
+            details.error = "InvalidSteamTicket".to_string();
+
+            assert!(!PlayFabError::Api(Box::new(details)).is_transient());
+        }
+
+        #[test]
+        fn network_errors_are_transient() {
+            assert!(PlayFabError::Timeout.is_transient());
+            assert!(PlayFabError::Network("reset".to_string()).is_transient());
+            assert!(PlayFabError::InvalidResponse {
+                status: 502,
+                message: "Bad gateway".to_string()
+            }
+            .is_transient());
+            assert!(!PlayFabError::Steam("Not running".to_string()).is_transient());
+        }
+    }
+
+    mod retry {
+        use crate::play_fab::{retry, ClientSettings, ErrorDetails, PlayFabError};
+        use std::cell::Cell;
+        use std::time::Duration;
+
+        fn settings(retries: u32) -> ClientSettings {
+            ClientSettings {
+                base_url: String::new(),
+                timeout: Duration::from_secs(1),
+                connect_timeout: Duration::from_secs(1),
+                retries,
+                retry_delay: Duration::ZERO,
+            }
+        }
+
+        #[tokio::test]
+        async fn retries_transient_errors() {
+            let attempts = Cell::new(0);
+
+            let result = retry(&settings(2), || async {
+                attempts.set(attempts.get() + 1);
+                match attempts.get() {
+                    1 => Err(PlayFabError::Timeout),
+                    _ => Ok(attempts.get()),
+                }
+            })
+            .await;
+
+            assert_eq!(result, Ok(2));
+        }
+
+        #[tokio::test]
+        async fn gives_up_after_retries() {
+            let attempts = Cell::new(0);
+
+            let result: Result<(), _> = retry(&settings(2), || async {
+                attempts.set(attempts.get() + 1);
+                Err(PlayFabError::Timeout)
+            })
+            .await;
+
+            assert_eq!(result, Err(PlayFabError::Timeout));
+            assert_eq!(attempts.get(), 3);
+        }
+
+        #[tokio::test]
+        async fn does_not_retry_other_errors() {
+            let attempts = Cell::new(0);
+
+            let result: Result<(), _> = retry(&settings(2), || async {
+                attempts.set(attempts.get() + 1);
+                Err(PlayFabError::Steam("Not running".to_string()))
+            })
+            .await;
+
+            assert!(result.is_err());
+            assert_eq!(attempts.get(), 1);
+        }
+
+        #[test]
+        fn doubles_delay() {
+            let settings = ClientSettings {
+                retry_delay: Duration::from_secs(1),
+                ..settings(3)
+            };
+
+            let delays: Vec<Duration> = (0..3)
+                .map(|i| settings.delay_before_retry(i, &PlayFabError::Timeout))
+                .collect();
+
+            assert_eq!(delays, [1, 2, 4].map(Duration::from_secs).to_vec());
+            assert_eq!(
+                settings.delay_before_retry(10, &PlayFabError::Timeout),
+                Duration::from_secs(30)
+            );
+        }
+
+        #[test]
+        fn uses_requested_delay() {
+            let error = PlayFabError::Api(Box::new(ErrorDetails {
+                code: 429,
+                status: "TooManyRequests".to_string(),
+                error: "APIClientRequestRateLimitExceeded".to_string(),
+                error_code: 1199,
+                error_message: String::new(),
+                error_details: None,
+                retry_after_seconds: Some(5),
+            }));
+
+            assert_eq!(
+                settings(1).delay_before_retry(0, &error),
+                Duration::from_secs(5)
+            );
+        }
+    }
+
     mod parse_ids {
         use crate::play_fab::parse_ids;
 
@@ -217,4 +764,88 @@ mod tests {
             assert_eq!(ids[1], 54321);
         }
     }
+
+    mod session_cache {
+        use crate::play_fab::{SessionCache, SessionTicket};
+        use crate::test_support::{test_dir, TestDir};
+        use std::fs;
+        use time::{Duration, OffsetDateTime};
+
+        fn test_cache(name: &str) -> (TestDir, SessionCache) {
+            let dir = test_dir(&format!("session-{name}"));
+            let cache = SessionCache::new(dir.join("playfab-session.json"));
+            (dir, cache)
+        }
+
+        fn ticket(expires_in: Duration) -> SessionTicket {
+            SessionTicket {
+                ticket: "session".to_string(),
+                expires_at: OffsetDateTime::now_utc() + expires_in,
+            }
+        }
+
+        #[test]
+        fn missing_file_is_none() {
+            let (_dir, cache) = test_cache("missing");
+
+            assert_eq!(cache.load(), None);
+        }
+
+        #[test]
+        fn survives_save_and_load() {
+            let (_dir, cache) = test_cache("round-trip");
+            let ticket = ticket(Duration::hours(1));
+
+            cache.save(&ticket).unwrap();
+
+            assert_eq!(cache.load(), Some(ticket));
+        }
+
+        #[test]
+        fn ignores_tickets_about_to_expire() {
+            let (_dir, cache) = test_cache("expired");
+
+            cache.save(&ticket(Duration::minutes(1))).unwrap();
+
+            assert_eq!(cache.load(), None);
+        }
+
+        #[test]
+        fn ignores_invalid_file() {
+            let (_dir, cache) = test_cache("invalid");
+            fs::write(&cache.path, "not json").unwrap();
+
+            assert_eq!(cache.load(), None);
+        }
+
+        #[test]
+        fn clear_removes_ticket() {
+            let (_dir, cache) = test_cache("clear");
+            cache.save(&ticket(Duration::hours(1))).unwrap();
+
+            cache.clear();
+
+            assert_eq!(cache.load(), None);
+        }
+
+        #[test]
+        fn debug_hides_ticket() {
+            let debug = format!("{:?}", ticket(Duration::hours(1)));
+
+            assert!(!debug.contains("\"session\""), "{debug}");
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn only_owner_can_read_file() {
+            use std::os::unix::fs::PermissionsExt;
+            let (_dir, cache) = test_cache("permissions");
+            fs::write(&cache.path, "{}").unwrap();
+
+            cache.save(&ticket(Duration::hours(1))).unwrap();
+
+            let mode = fs::metadata(&cache.path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
 }
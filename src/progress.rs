@@ -0,0 +1,297 @@
+use crate::level_id::LevelId;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReadSource {
+    PlayFab,
+    LogFile,
+    Manual,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadRecord {
+    #[serde(with = "time::serde::rfc3339")]
+    pub first_seen: OffsetDateTime,
+    pub source: ReadSource,
+    pub level: Option<LevelId>,
+    /// Set once PlayFab reports a log that was first seen somewhere else
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub play_fab_confirmed: bool,
+}
+
+impl ReadRecord {
+    pub fn now(source: ReadSource, level: Option<LevelId>) -> Self {
+        Self {
+            first_seen: OffsetDateTime::now_utc(),
+            source,
+            level,
+            play_fab_confirmed: false,
+        }
+    }
+
+    /// Whether PlayFab, which the game itself uses, has reported the log as read
+    pub fn confirmed_by_play_fab(&self) -> bool {
+        self.source == ReadSource::PlayFab || self.play_fab_confirmed
+    }
+
+    /// Combines another sighting of the same log into this one. The earliest time and any known
+    /// level are kept, and PlayFab wins as the source. Returns whether anything changed.
+    pub fn combine(&mut self, other: ReadRecord) -> bool {
+        let before = self.clone();
+        self.first_seen = self.first_seen.min(other.first_seen);
+        if other.source == ReadSource::PlayFab {
+            self.source = ReadSource::PlayFab;
+        }
+        self.level = self.level.or(other.level);
+        self.play_fab_confirmed |= other.play_fab_confirmed;
+
+        *self != before
+    }
+}
+
+/// Read logs seen across every run of the tracker, kept so progress survives deleted game logs
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ProgressStore {
+    #[serde(skip)]
+    path: PathBuf,
+    logs: BTreeMap<u32, ReadRecord>,
+}
+
+impl ProgressStore {
+    pub fn path_in(state_dir: &Path) -> PathBuf {
+        state_dir.join("progress.json")
+    }
+
+    /// Loads the store at `path`. A missing file is treated as no progress. A file that can't
+    /// be parsed is moved aside to `<path>.bak` so it can be recovered by hand, and the store
+    /// starts empty.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut store = if path.exists() {
+            let json = fs::read_to_string(path)
+                .with_context(|| format!("Couldn't read progress file '{}'", path.display()))?;
+            match serde_json::from_str::<ProgressStore>(&json) {
+                Ok(store) => store,
+                Err(e) => {
+                    let backup = backup_path(path);
+                    log::warn!(
+                        "Failed to parse progress file '{}', moving it to '{}' - {e}",
+                        path.display(),
+                        backup.display()
+                    );
+                    fs::rename(path, &backup).with_context(|| {
+                        format!("Couldn't move progress file to '{}'", backup.display())
+                    })?;
+                    ProgressStore::default()
+                }
+            }
+        } else {
+            ProgressStore::default()
+        };
+        store.path = path.to_path_buf();
+
+        Ok(store)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Couldn't create directory '{}'", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&self.path, json)
+            .with_context(|| format!("Couldn't write progress file '{}'", self.path.display()))
+    }
+
+    /// Records a read log. Returns false if the log was already recorded, in which case the
+    /// original record is kept.
+    pub fn record(&mut self, id: u32, record: ReadRecord) -> bool {
+        if self.logs.contains_key(&id) {
+            return false;
+        }
+
+        self.logs.insert(id, record);
+        true
+    }
+
+    /// Records every observation, combining it with the log's existing record if there is one.
+    /// Returns how many logs were added or changed.
+    pub fn merge(&mut self, observations: HashMap<u32, ReadRecord>) -> usize {
+        observations
+            .into_iter()
+            .filter(|(id, record)| match self.logs.get_mut(id) {
+                Some(existing) => existing.combine(record.clone()),
+                None => self.record(*id, record.clone()),
+            })
+            .count()
+    }
+
+    /// Marks recorded logs that PlayFab reported as read and returns how many weren't already
+    pub fn confirm_play_fab(&mut self, ids: &[u32]) -> usize {
+        let mut confirmed = 0;
+        for id in ids {
+            match self.logs.get_mut(id) {
+                Some(record) if !record.confirmed_by_play_fab() => {
+                    record.play_fab_confirmed = true;
+                    confirmed += 1;
+                }
+                _ => {}
+            }
+        }
+
+        confirmed
+    }
+
+    pub fn get(&self, id: u32) -> Option<&ReadRecord> {
+        self.logs.get(&id)
+    }
+
+    pub fn read_ids(&self) -> HashSet<u32> {
+        self.logs.keys().copied().collect()
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    mod progress_store {
+        use crate::level_id::LevelId;
+        use crate::progress::{ProgressStore, ReadRecord, ReadSource};
+        use crate::test_support::test_dir;
+        use std::collections::HashMap;
+        use std::fs;
+        use time::macros::datetime;
+
+        #[test]
+        fn missing_file_is_empty() {
+            let dir = test_dir("progress-missing");
+
+            let store = ProgressStore::load(&dir.join("progress.json")).unwrap();
+
+            assert!(store.read_ids().is_empty());
+        }
+
+        #[test]
+        fn moves_invalid_file_aside() {
+            let dir = test_dir("progress-invalid");
+            let path = dir.join("progress.json");
+            fs::write(&path, "not json").unwrap();
+
+            let store = ProgressStore::load(&path).unwrap();
+
+            assert!(store.read_ids().is_empty());
+            assert!(!path.exists());
+            assert_eq!(
+                fs::read_to_string(dir.join("progress.json.bak")).unwrap(),
+                "not json"
+            );
+        }
+
+        #[test]
+        fn keeps_first_record() {
+            let mut store = ProgressStore::default();
+            let first = ReadRecord::now(ReadSource::LogFile, Some(LevelId::new(1, 'A', 1)));
+
+            store.record(1, first.clone());
+            let added = store.record(1, ReadRecord::now(ReadSource::PlayFab, None));
+
+            assert!(!added);
+            assert_eq!(store.get(1), Some(&first));
+        }
+
+        #[test]
+        fn merge_counts_new_logs() {
+            let mut store = ProgressStore::default();
+            store.record(1, ReadRecord::now(ReadSource::Manual, None));
+
+            let added = store.merge(HashMap::from([
+                (1, ReadRecord::now(ReadSource::Manual, None)),
+                (2, ReadRecord::now(ReadSource::PlayFab, None)),
+            ]));
+
+            assert_eq!(added, 1);
+            assert_eq!(store.get(1).unwrap().source, ReadSource::Manual);
+        }
+
+        #[test]
+        fn merge_keeps_earliest_time_and_play_fab_source() {
+            let level = Some(LevelId::new(1, 'A', 1));
+            let log_file = ReadRecord {
+                first_seen: datetime!(2024-01-01 12:00 UTC),
+                source: ReadSource::LogFile,
+                level,
+                play_fab_confirmed: false,
+            };
+            let play_fab = ReadRecord {
+                first_seen: datetime!(2024-02-01 12:00 UTC),
+                source: ReadSource::PlayFab,
+                level: None,
+                play_fab_confirmed: false,
+            };
+            let expected = ReadRecord {
+                first_seen: datetime!(2024-01-01 12:00 UTC),
+                source: ReadSource::PlayFab,
+                level,
+                play_fab_confirmed: false,
+            };
+
+            for (first, second) in [
+                (log_file.clone(), play_fab.clone()),
+                (play_fab.clone(), log_file.clone()),
+            ] {
+                let mut store = ProgressStore::default();
+                store.merge(HashMap::from([(1, first)]));
+
+                let changed = store.merge(HashMap::from([(1, second)]));
+
+                assert_eq!(changed, 1);
+                assert_eq!(store.get(1), Some(&expected));
+            }
+        }
+
+        #[test]
+        fn confirms_logs_seen_elsewhere() {
+            let mut store = ProgressStore::default();
+            store.record(1, ReadRecord::now(ReadSource::LogFile, None));
+            store.record(2, ReadRecord::now(ReadSource::PlayFab, None));
+            store.record(3, ReadRecord::now(ReadSource::LogFile, None));
+
+            let confirmed = store.confirm_play_fab(&[1, 2, 4]);
+
+            assert_eq!(confirmed, 1);
+            assert!(store.get(1).unwrap().confirmed_by_play_fab());
+            assert_eq!(store.get(1).unwrap().source, ReadSource::LogFile);
+            assert!(store.get(2).unwrap().confirmed_by_play_fab());
+            assert!(!store.get(3).unwrap().confirmed_by_play_fab());
+            assert_eq!(store.get(4), None);
+        }
+
+        #[test]
+        fn survives_save_and_load() {
+            let dir = test_dir("progress-round-trip");
+            let path = dir.join("progress.json");
+            let mut store = ProgressStore::load(&path).unwrap();
+            store.record(
+                1,
+                ReadRecord::now(ReadSource::LogFile, Some(LevelId::new(7, 'D', 1))),
+            );
+            store.confirm_play_fab(&[1]);
+
+            store.save().unwrap();
+            let loaded = ProgressStore::load(&path).unwrap();
+
+            assert_eq!(loaded.get(1), store.get(1));
+        }
+    }
+}
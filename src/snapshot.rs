@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use time::{Duration, OffsetDateTime};
+
+/// The read logs from the last time PlayFab was reached, used when it can't be
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayFabSnapshot {
+    #[serde(with = "time::serde::rfc3339")]
+    pub fetched_at: OffsetDateTime,
+    pub read_log_ids: Vec<u32>,
+}
+
+impl PlayFabSnapshot {
+    pub fn now(read_log_ids: Vec<u32>) -> Self {
+        Self {
+            fetched_at: OffsetDateTime::now_utc(),
+            read_log_ids,
+        }
+    }
+
+    pub fn path_in(state_dir: &Path) -> PathBuf {
+        state_dir.join("playfab.json")
+    }
+
+    /// Loads the snapshot at `path`, if one has been saved
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Couldn't read PlayFab snapshot '{}'", path.display()))?;
+        serde_json::from_str(&json)
+            .map(Some)
+            .with_context(|| format!("Failed to parse PlayFab snapshot '{}'", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Couldn't create directory '{}'", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+            .with_context(|| format!("Couldn't write PlayFab snapshot '{}'", path.display()))
+    }
+}
+
+/// How long ago `time` was in the largest whole unit, such as "3 hours ago"
+pub fn describe_age(time: OffsetDateTime, now: OffsetDateTime) -> String {
+    let age = now - time;
+    let (count, unit) = if age < Duration::MINUTE {
+        return "just now".to_string();
+    } else if age < Duration::HOUR {
+        (age.whole_minutes(), "minute")
+    } else if age < Duration::DAY {
+        (age.whole_hours(), "hour")
+    } else {
+        (age.whole_days(), "day")
+    };
+
+    if count == 1 {
+        format!("1 {unit} ago")
+    } else {
+        format!("{count} {unit}s ago")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod play_fab_snapshot {
+        use crate::snapshot::PlayFabSnapshot;
+        use crate::test_support::test_dir;
+        use std::fs;
+        use time::macros::datetime;
+
+        #[test]
+        fn missing_file_is_none() {
+            let dir = test_dir("snapshot-missing");
+
+            let snapshot = PlayFabSnapshot::load(&dir.join("playfab.json")).unwrap();
+
+            assert_eq!(snapshot, None);
+        }
+
+        #[test]
+        fn survives_save_and_load() {
+            let dir = test_dir("snapshot-round-trip");
+            let path = dir.join("playfab.json");
+            let snapshot = PlayFabSnapshot {
+                fetched_at: datetime!(2024-02-14 12:30:15 UTC),
+                read_log_ids: vec![1, 2],
+            };
+
+            snapshot.save(&path).unwrap();
+            let loaded = PlayFabSnapshot::load(&path).unwrap();
+
+            assert_eq!(loaded, Some(snapshot));
+        }
+
+        #[test]
+        fn reports_invalid_file() {
+            let dir = test_dir("snapshot-invalid");
+            let path = dir.join("playfab.json");
+            fs::write(&path, "not json").unwrap();
+
+            assert!(PlayFabSnapshot::load(&path).is_err());
+        }
+    }
+
+    mod describe_age {
+        use crate::snapshot::describe_age;
+        use time::macros::datetime;
+        use time::Duration;
+
+        #[test]
+        fn uses_largest_unit() {
+            let now = datetime!(2024-02-14 12:00 UTC);
+
+            assert_eq!(describe_age(now - Duration::seconds(20), now), "just now");
+            assert_eq!(
+                describe_age(now - Duration::minutes(1), now),
+                "1 minute ago"
+            );
+            assert_eq!(
+                describe_age(now - Duration::minutes(59), now),
+                "59 minutes ago"
+            );
+            assert_eq!(describe_age(now - Duration::hours(5), now), "5 hours ago");
+            assert_eq!(describe_age(now - Duration::days(3), now), "3 days ago");
+        }
+    }
+}
@@ -1,51 +1,418 @@
+use anyhow::{anyhow, Context, Result};
 use std::env;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::fs;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::CharIndices;
 
 const GTFO_APP_ID: u32 = 493520;
 
-pub fn find_proton_app_data_path() -> Option<PathBuf> {
-    let home = env::var_os("HOME").map(PathBuf::from)?;
+/// Returns every Proton AppData directory GTFO could be using, most likely first. Checks
+/// `STEAM_COMPAT_DATA_PATH`, then every library of the native, Flatpak, Snap and `STEAM_DIR`
+/// Steam installs.
+pub fn find_proton_app_data_paths() -> Vec<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let steam_dir = env::var_os("STEAM_DIR").map(PathBuf::from);
+    let compat_data_path = env::var_os("STEAM_COMPAT_DATA_PATH").map(PathBuf::from);
 
-    [".steam/steam/steamapps/", ".local/share/Steam/steamapps/"]
-        .iter()
-        .find_map(|p| {
-            let path = home.join(p).join("libraryfolders.vdf");
-            let file = File::open(path).ok()?;
-            let lines = BufReader::new(file).lines().map_while(Result::ok);
+    proton_app_data_paths(home.as_deref(), steam_dir, compat_data_path)
+}
 
-            parse_library_path(GTFO_APP_ID, lines).map(|p| {
-                p.join(format!(
-                    "steamapps/compatdata/{GTFO_APP_ID}/pfx/drive_c/users/steamuser/AppData"
-                ))
+fn proton_app_data_paths(
+    home: Option<&Path>,
+    steam_dir: Option<PathBuf>,
+    compat_data_path: Option<PathBuf>,
+) -> Vec<PathBuf> {
+    let compat_data_dirs = compat_data_path.map(app_compat_data_dir).into_iter().chain(
+        steam_roots(home, steam_dir)
+            .into_iter()
+            .flat_map(|root| {
+                let vdf_path = root.join("steamapps/libraryfolders.vdf");
+                let vdf = fs::read_to_string(&vdf_path).ok()?;
+                log::debug!("Found Steam library list '{}'", vdf_path.display());
+                Some(parse_library_paths(GTFO_APP_ID, &vdf))
             })
-        })
+            .flatten()
+            .map(|library| {
+                fs::canonicalize(&library)
+                    .unwrap_or(library)
+                    .join(format!("steamapps/compatdata/{GTFO_APP_ID}"))
+            }),
+    );
+
+    let mut seen = Vec::new();
+    for dir in compat_data_dirs {
+        // ~/.steam/steam is usually a symlink to another root
+        let dir = fs::canonicalize(&dir).unwrap_or(dir);
+        if !seen.contains(&dir) {
+            seen.push(dir);
+        }
+    }
+
+    seen.into_iter()
+        .map(|dir| dir.join("pfx/drive_c/users/steamuser/AppData"))
+        .collect()
+}
+
+/// `STEAM_COMPAT_DATA_PATH` normally points at the game's own compatdata directory but
+/// also accept the directory containing every game's
+fn app_compat_data_dir(path: PathBuf) -> PathBuf {
+    if path.ends_with(GTFO_APP_ID.to_string()) {
+        path
+    } else {
+        path.join(GTFO_APP_ID.to_string())
+    }
+}
+
+fn steam_roots(home: Option<&Path>, steam_dir: Option<PathBuf>) -> Vec<PathBuf> {
+    const HOME_ROOTS: [&str; 6] = [
+        ".steam/steam",
+        ".local/share/Steam",
+        ".var/app/com.valvesoftware.Steam/.steam/steam",
+        ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+        "snap/steam/common/.steam/steam",
+        "snap/steam/common/.local/share/Steam",
+    ];
+
+    steam_dir
+        .into_iter()
+        .chain(
+            home.into_iter()
+                .flat_map(|home| HOME_ROOTS.iter().map(move |root| home.join(root))),
+        )
+        .collect()
+}
+
+fn parse_library_paths(app_id: u32, vdf: &str) -> Vec<PathBuf> {
+    let folders = match LibraryFolder::parse_all(vdf) {
+        Ok(folders) => folders,
+        Err(e) => {
+            log::warn!("Couldn't parse libraryfolders.vdf - {e:?}");
+            return Vec::new();
+        }
+    };
+
+    folders
+        .into_iter()
+        .filter(|f| f.has_app(app_id))
+        .map(|f| f.path)
+        .collect()
+}
+
+/// A node in a Valve KeyValues (VDF/ACF) document
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyValues {
+    Value(String),
+    Section(Vec<(String, KeyValues)>),
+}
+
+impl KeyValues {
+    /// Parses a document into a section holding its top level keys
+    pub fn parse(input: &str) -> Result<KeyValues> {
+        let mut tokens = Tokenizer::new(input);
+        parse_section(&mut tokens, false).map(KeyValues::Section)
+    }
+
+    /// Returns the first child with the given key. Keys are case-insensitive.
+    pub fn get(&self, key: &str) -> Option<&KeyValues> {
+        self.entries()
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(KeyValues::as_str)
+    }
+
+    pub fn get_u32(&self, key: &str) -> Option<u32> {
+        self.get_str(key).and_then(|s| s.parse().ok())
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            KeyValues::Value(v) => Some(v),
+            KeyValues::Section(_) => None,
+        }
+    }
+
+    /// Child keys and values in document order. Empty for plain values.
+    pub fn entries(&self) -> &[(String, KeyValues)] {
+        match self {
+            KeyValues::Value(_) => &[],
+            KeyValues::Section(entries) => entries,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Open,
+    Close,
 }
 
-fn parse_library_path(app_id: u32, lines: impl Iterator<Item = String>) -> Option<PathBuf> {
-    let id = format!("\"{}", app_id);
-    let mut last_seen_path: Option<PathBuf> = None;
+struct Tokenizer<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            chars: input.char_indices().peekable(),
+            line: 1,
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>> {
+        while let Some(&(_, c)) = self.chars.peek() {
+            match c {
+                '\n' => {
+                    self.line += 1;
+                    self.chars.next();
+                }
+                c if c.is_whitespace() => {
+                    self.chars.next();
+                }
+                '/' => self.skip_comment()?,
+                // Platform conditionals like [$WIN32] aren't needed for Steam's config files
+                '[' => self.skip_conditional()?,
+                '{' => {
+                    self.chars.next();
+                    return Ok(Some(Token::Open));
+                }
+                '}' => {
+                    self.chars.next();
+                    return Ok(Some(Token::Close));
+                }
+                '"' => return self.quoted().map(|s| Some(Token::Str(s))),
+                _ => return Ok(Some(Token::Str(self.unquoted()))),
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn skip_comment(&mut self) -> Result<()> {
+        self.chars.next();
+        if !matches!(self.chars.next(), Some((_, '/'))) {
+            return Err(anyhow!("Expected comment on line {}", self.line));
+        }
+        while let Some(&(_, c)) = self.chars.peek() {
+            if c == '\n' {
+                break;
+            }
+            self.chars.next();
+        }
+
+        Ok(())
+    }
+
+    fn skip_conditional(&mut self) -> Result<()> {
+        let line = self.line;
+        for (_, c) in self.chars.by_ref() {
+            if c == ']' {
+                return Ok(());
+            }
+        }
+
+        Err(anyhow!("Unterminated conditional on line {line}"))
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let line = self.line;
+        let mut value = String::new();
+        self.chars.next();
 
-    for line in lines {
-        let trimmed = line.trim_start();
-        if trimmed.starts_with("\"path\"") {
-            last_seen_path = line
-                .splitn(4, '"')
-                .last()
-                .map(|s| PathBuf::from(&s[..s.len() - 1]))
-        } else if trimmed.starts_with(&id) {
-            return last_seen_path;
+        while let Some((_, c)) = self.chars.next() {
+            match c {
+                '"' => return Ok(value),
+                '\\' => match self.chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 't')) => value.push('\t'),
+                    Some((_, escaped @ ('\\' | '"'))) => value.push(escaped),
+                    // Not an escape sequence so keep the backslash
+                    Some((_, other)) => {
+                        value.push('\\');
+                        value.push(other);
+                    }
+                    None => break,
+                },
+                '\n' => {
+                    self.line += 1;
+                    value.push(c);
+                }
+                _ => value.push(c),
+            }
         }
+
+        Err(anyhow!("Unterminated string starting on line {line}"))
+    }
+
+    fn unquoted(&mut self) -> String {
+        let mut value = String::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            if c.is_whitespace() || matches!(c, '{' | '}' | '"') {
+                break;
+            }
+            value.push(c);
+            self.chars.next();
+        }
+
+        value
+    }
+}
+
+fn parse_section(tokens: &mut Tokenizer, nested: bool) -> Result<Vec<(String, KeyValues)>> {
+    let mut entries = Vec::new();
+
+    loop {
+        let key = match tokens.next_token()? {
+            Some(Token::Str(key)) => key,
+            Some(Token::Close) if nested => return Ok(entries),
+            None if !nested => return Ok(entries),
+            Some(Token::Close) => return Err(anyhow!("Unexpected '}}' on line {}", tokens.line)),
+            Some(Token::Open) => return Err(anyhow!("Unexpected '{{' on line {}", tokens.line)),
+            None => return Err(anyhow!("Missing closing '}}'")),
+        };
+
+        let value = match tokens.next_token()? {
+            Some(Token::Str(value)) => KeyValues::Value(value),
+            Some(Token::Open) => KeyValues::Section(parse_section(tokens, true)?),
+            _ => {
+                return Err(anyhow!(
+                    "Missing value for key '{key}' on line {}",
+                    tokens.line
+                ))
+            }
+        };
+
+        entries.push((key, value));
+    }
+}
+
+/// A Steam library from libraryfolders.vdf
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryFolder {
+    pub path: PathBuf,
+    pub apps: Vec<u32>,
+}
+
+impl LibraryFolder {
+    pub fn parse_all(vdf: &str) -> Result<Vec<LibraryFolder>> {
+        let root = KeyValues::parse(vdf)?;
+        let folders = root
+            .get("libraryfolders")
+            .ok_or_else(|| anyhow!("Missing 'libraryfolders' section"))?;
+
+        Ok(folders
+            .entries()
+            .iter()
+            .filter(|(key, _)| key.parse::<u32>().is_ok())
+            .filter_map(|(_, folder)| match folder {
+                // Older versions only list the library path
+                KeyValues::Value(path) => Some(LibraryFolder {
+                    path: PathBuf::from(path),
+                    apps: Vec::new(),
+                }),
+                KeyValues::Section(_) => Some(LibraryFolder {
+                    path: PathBuf::from(folder.get_str("path")?),
+                    apps: folder
+                        .get("apps")
+                        .map(|apps| {
+                            apps.entries()
+                                .iter()
+                                .filter_map(|(id, _)| id.parse().ok())
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                }),
+            })
+            .collect())
+    }
+
+    /// Checks the library's app list, falling back to looking for the app's manifest since
+    /// older versions of libraryfolders.vdf don't list apps
+    pub fn has_app(&self, app_id: u32) -> bool {
+        self.apps.contains(&app_id) || self.manifest_path(app_id).exists()
+    }
+
+    pub fn manifest_path(&self, app_id: u32) -> PathBuf {
+        self.path
+            .join("steamapps")
+            .join(format!("appmanifest_{app_id}.acf"))
+    }
+}
+
+/// An installed app from steamapps/appmanifest_*.acf
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppManifest {
+    pub app_id: u32,
+    pub name: String,
+    pub install_dir: String,
+}
+
+impl AppManifest {
+    pub fn parse(acf: &str) -> Result<AppManifest> {
+        let root = KeyValues::parse(acf)?;
+        let state = root
+            .get("AppState")
+            .ok_or_else(|| anyhow!("Missing 'AppState' section"))?;
+
+        Ok(AppManifest {
+            app_id: state.get_u32("appid").context("Missing 'appid'")?,
+            name: state.get_str("name").unwrap_or_default().to_string(),
+            install_dir: state
+                .get_str("installdir")
+                .context("Missing 'installdir'")?
+                .to_string(),
+        })
     }
 
-    None
+    pub fn load(path: &Path) -> Result<AppManifest> {
+        let acf = fs::read_to_string(path)
+            .with_context(|| format!("Couldn't read '{}'", path.display()))?;
+        AppManifest::parse(&acf).with_context(|| format!("Couldn't parse '{}'", path.display()))
+    }
+}
+
+/// A Steam account that has logged in on this machine, from config/loginusers.vdf
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginUser {
+    pub steam_id: u64,
+    pub account_name: String,
+    pub persona_name: String,
+    pub most_recent: bool,
+}
+
+impl LoginUser {
+    pub fn parse_all(vdf: &str) -> Result<Vec<LoginUser>> {
+        let root = KeyValues::parse(vdf)?;
+        let users = root
+            .get("users")
+            .ok_or_else(|| anyhow!("Missing 'users' section"))?;
+
+        Ok(users
+            .entries()
+            .iter()
+            .filter_map(|(id, user)| {
+                Some(LoginUser {
+                    steam_id: id.parse().ok()?,
+                    account_name: user.get_str("AccountName")?.to_string(),
+                    persona_name: user.get_str("PersonaName").unwrap_or_default().to_string(),
+                    most_recent: user.get_str("MostRecent") == Some("1"),
+                })
+            })
+            .collect())
+    }
 }
 
 #[cfg(test)]
 mod test {
-    mod parse_library_path {
-        use crate::steam::{parse_library_path, GTFO_APP_ID};
+    mod parse_library_paths {
+        use crate::steam::{parse_library_paths, GTFO_APP_ID};
+        use std::path::PathBuf;
 
         #[test]
         fn finds_path_if_one_game_library() {
@@ -54,16 +421,14 @@ mod test {
                 r#"
                 "libraryfolders"
                 {{
-                    {}                
+                    {}
                 }}"#,
                 generate_library(path, 0, GTFO_APP_ID)
             );
-            let lines = vdf.lines().map(String::from);
 
-            let result = parse_library_path(GTFO_APP_ID, lines);
+            let result = parse_library_paths(GTFO_APP_ID, &vdf);
 
-            assert!(result.is_some());
-            assert_eq!(result.unwrap().to_string_lossy(), path);
+            assert_eq!(result, vec![PathBuf::from(path)]);
         }
 
         #[test]
@@ -79,12 +444,40 @@ mod test {
                 generate_library("/wrong/path", 0, 0),
                 generate_library(expected_path, 1, GTFO_APP_ID),
             );
-            let lines = vdf.lines().map(String::from);
 
-            let result = parse_library_path(GTFO_APP_ID, lines);
+            let result = parse_library_paths(GTFO_APP_ID, &vdf);
+
+            assert_eq!(result, vec![PathBuf::from(expected_path)]);
+        }
+
+        #[test]
+        fn finds_path_if_apps_listed_before_path() {
+            let vdf = include_str!("../tests/fixtures/steam/libraryfolders.vdf");
+
+            let result = parse_library_paths(GTFO_APP_ID, vdf);
 
-            assert!(result.is_some());
-            assert_eq!(result.unwrap().to_string_lossy(), expected_path);
+            assert_eq!(result, vec![PathBuf::from("/mnt/games/SteamLibrary")]);
+        }
+
+        #[test]
+        fn finds_every_library_with_game() {
+            let vdf = format!(
+                r#"
+                "libraryfolders"
+                {{
+                    {}
+                    {}
+                }}"#,
+                generate_library("/first/path", 0, GTFO_APP_ID),
+                generate_library("/second/path", 1, GTFO_APP_ID),
+            );
+
+            let result = parse_library_paths(GTFO_APP_ID, &vdf);
+
+            assert_eq!(
+                result,
+                vec![PathBuf::from("/first/path"), PathBuf::from("/second/path")]
+            );
         }
 
         fn generate_library(path: &str, id: u8, app_id: u32) -> String {
@@ -107,4 +500,248 @@ mod test {
             )
         }
     }
+    mod proton_app_data_paths {
+        use crate::steam::{proton_app_data_paths, GTFO_APP_ID};
+        use crate::test_support::test_dir;
+        use std::fs;
+        use std::path::{Path, PathBuf};
+
+        fn write_library_folders(root: &Path, libraries: &[&Path]) {
+            let folders: String = libraries
+                .iter()
+                .enumerate()
+                .map(|(i, path)| {
+                    format!(
+                        r#""{i}" {{ "path" "{}" "apps" {{ "{GTFO_APP_ID}" "1" }} }}"#,
+                        path.display()
+                    )
+                })
+                .collect();
+            let steamapps = root.join("steamapps");
+            fs::create_dir_all(&steamapps).unwrap();
+            fs::write(
+                steamapps.join("libraryfolders.vdf"),
+                format!(r#""libraryfolders" {{ {folders} }}"#),
+            )
+            .unwrap();
+        }
+
+        fn app_data(library: &Path) -> PathBuf {
+            library.join(format!(
+                "steamapps/compatdata/{GTFO_APP_ID}/pfx/drive_c/users/steamuser/AppData"
+            ))
+        }
+
+        #[test]
+        fn finds_flatpak_libraries() {
+            let home = test_dir("steam-flatpak");
+            let root = home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam");
+            let library = home.join("games");
+            write_library_folders(&root, &[&root, &library]);
+
+            let paths = proton_app_data_paths(Some(&home), None, None);
+
+            assert_eq!(paths, vec![app_data(&root), app_data(&library)]);
+        }
+
+        #[test]
+        fn finds_snap_libraries() {
+            let home = test_dir("steam-snap");
+            let root = home.join("snap/steam/common/.local/share/Steam");
+            write_library_folders(&root, &[&root]);
+
+            let paths = proton_app_data_paths(Some(&home), None, None);
+
+            assert_eq!(paths, vec![app_data(&root)]);
+        }
+
+        #[test]
+        fn finds_steam_dir_libraries() {
+            let home = test_dir("steam-steam-dir");
+            let root = home.join("custom/Steam");
+            write_library_folders(&root, &[&root]);
+
+            let paths = proton_app_data_paths(None, Some(root.clone()), None);
+
+            assert_eq!(paths, vec![app_data(&root)]);
+        }
+
+        #[test]
+        fn compat_data_path_comes_first() {
+            let home = test_dir("steam-compat-data");
+            let root = home.join(".local/share/Steam");
+            write_library_folders(&root, &[&root]);
+            let compat_data = home.join(format!("prefixes/{GTFO_APP_ID}"));
+
+            let paths = proton_app_data_paths(Some(&home), None, Some(compat_data.clone()));
+
+            assert_eq!(
+                paths,
+                vec![
+                    compat_data.join("pfx/drive_c/users/steamuser/AppData"),
+                    app_data(&root)
+                ]
+            );
+        }
+
+        #[test]
+        fn removes_duplicate_libraries() {
+            let home = test_dir("steam-duplicates");
+            let root = home.join(".local/share/Steam");
+            write_library_folders(&root, &[&root]);
+            fs::create_dir_all(home.join(".steam")).unwrap();
+            std::os::unix::fs::symlink(&root, home.join(".steam/steam")).unwrap();
+
+            let paths = proton_app_data_paths(Some(&home), None, None);
+
+            assert_eq!(paths, vec![app_data(&root)]);
+        }
+    }
+    mod key_values {
+        use crate::steam::KeyValues;
+
+        #[test]
+        fn parses_nested_sections() {
+            let kv = KeyValues::parse(r#""a" { "b" { "c" "d" } }"#).unwrap();
+
+            let value = kv
+                .get("a")
+                .and_then(|a| a.get("b"))
+                .and_then(|b| b.get_str("c"));
+
+            assert_eq!(value, Some("d"));
+        }
+
+        #[test]
+        fn keys_are_case_insensitive() {
+            let kv = KeyValues::parse(r#""Key" "value""#).unwrap();
+
+            assert_eq!(kv.get_str("KEY"), Some("value"));
+        }
+
+        #[test]
+        fn unescapes_quoted_strings() {
+            let kv = KeyValues::parse(r#""path" "C:\\Games\\\"Steam\"\tLib""#).unwrap();
+
+            assert_eq!(kv.get_str("path"), Some("C:\\Games\\\"Steam\"\tLib"));
+        }
+
+        #[test]
+        fn skips_comments_and_conditionals() {
+            let kv = KeyValues::parse(
+                r#"
+                // comment
+                "a" "1" [$WIN32]
+                b 2 // trailing comment
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(kv.get_str("a"), Some("1"));
+            assert_eq!(kv.get_str("b"), Some("2"));
+        }
+
+        #[test]
+        fn fails_on_unclosed_section() {
+            let result = KeyValues::parse(r#""a" { "b" "c""#);
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn fails_on_unterminated_string() {
+            let result = KeyValues::parse(r#""a" "b"#);
+
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn fails_on_missing_value() {
+            let result = KeyValues::parse(r#""a" { "b" }"#);
+
+            assert!(result.is_err());
+        }
+    }
+    mod library_folder {
+        use crate::steam::{LibraryFolder, GTFO_APP_ID};
+        use std::path::PathBuf;
+
+        #[test]
+        fn parses_linux_libraries() {
+            let vdf = include_str!("../tests/fixtures/steam/libraryfolders.vdf");
+
+            let folders = LibraryFolder::parse_all(vdf).unwrap();
+
+            assert_eq!(folders.len(), 2);
+            assert_eq!(
+                folders[0].path,
+                PathBuf::from("/home/user/.local/share/Steam")
+            );
+            assert_eq!(folders[0].apps, vec![228980, 1391110, 1493710]);
+            assert_eq!(folders[1].apps, vec![GTFO_APP_ID, 1245620]);
+        }
+
+        #[test]
+        fn parses_windows_libraries() {
+            let vdf = include_str!("../tests/fixtures/steam/libraryfolders_windows.vdf");
+
+            let folders = LibraryFolder::parse_all(vdf).unwrap();
+
+            assert_eq!(
+                folders[0].path,
+                PathBuf::from(r"C:\Program Files (x86)\Steam")
+            );
+            assert_eq!(
+                folders[1].path,
+                PathBuf::from(r#"D:\Games\"Steam" Library"#)
+            );
+        }
+
+        #[test]
+        fn parses_legacy_libraries() {
+            let vdf = r#"
+            "LibraryFolders"
+            {
+                "TimeNextStatsReport"		"1616353220"
+                "ContentStatsID"		"-1394585390434455553"
+                "1"		"/mnt/games/SteamLibrary"
+            }"#;
+
+            let folders = LibraryFolder::parse_all(vdf).unwrap();
+
+            assert_eq!(folders.len(), 1);
+            assert_eq!(folders[0].path, PathBuf::from("/mnt/games/SteamLibrary"));
+        }
+    }
+    mod app_manifest {
+        use crate::steam::{AppManifest, GTFO_APP_ID};
+
+        #[test]
+        fn parses_manifest() {
+            let acf = include_str!("../tests/fixtures/steam/appmanifest_493520.acf");
+
+            let manifest = AppManifest::parse(acf).unwrap();
+
+            assert_eq!(manifest.app_id, GTFO_APP_ID);
+            assert_eq!(manifest.name, "GTFO");
+            assert_eq!(manifest.install_dir, "GTFO");
+        }
+    }
+    mod login_user {
+        use crate::steam::LoginUser;
+
+        #[test]
+        fn parses_users() {
+            let vdf = include_str!("../tests/fixtures/steam/loginusers.vdf");
+
+            let users = LoginUser::parse_all(vdf).unwrap();
+
+            assert_eq!(users.len(), 2);
+            assert_eq!(users[0].steam_id, 76561197960287930);
+            assert_eq!(users[0].account_name, "gabelogannewell");
+            assert_eq!(users[0].persona_name, "Rabscuttle");
+            assert!(users[0].most_recent);
+            assert!(!users[1].most_recent);
+        }
+    }
 }
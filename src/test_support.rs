@@ -0,0 +1,94 @@
+//! Fixtures shared by the unit tests
+
+use crate::game_data::{Location, StoryLog};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// A location in `level`, such as R1A1
+pub fn location(level: &str, zones: Vec<u16>, name: &str) -> Location {
+    Location {
+        level: level.parse().unwrap(),
+        zones,
+        name: name.to_string(),
+        audio_id: None,
+    }
+}
+
+/// A log called `name` in zone 1 of each of `levels`
+pub fn story_log(id: u32, name: &str, levels: &[&str]) -> StoryLog {
+    StoryLog {
+        id,
+        locations: levels
+            .iter()
+            .map(|level| location(level, vec![1], name))
+            .collect(),
+    }
+}
+
+/// An empty temporary directory that's deleted when dropped. Every call gets its own
+/// directory, so tests running in parallel or in other processes don't share files.
+pub struct TestDir(PathBuf);
+
+pub fn test_dir(name: &str) -> TestDir {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = std::env::temp_dir().join(format!(
+        "gtfo-log-tracker-{name}-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    TestDir(dir)
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Starts a server that answers a single request with a 200 and `body`. Returns its URL.
+pub fn serve_once(body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            match line.trim_end().split_once(": ") {
+                Some((name, value)) if name.eq_ignore_ascii_case("content-length") => {
+                    length = value.parse().unwrap();
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+        reader.read_exact(&mut vec![0; length]).unwrap();
+
+        let _ = write!(
+            reader.into_inner(),
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+    });
+
+    url
+}
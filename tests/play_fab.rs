@@ -0,0 +1,401 @@
+//! Runs the PlayFab client against a local stand-in for the PlayFab API
+
+use gtfo_log_tracker::play_fab::{
+    get_read_log_ids, Client, ClientSettings, PlayFabError, SessionCache, TicketProvider,
+};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const LOGIN_OK: &str = r#"{"code": 200, "status": "OK", "data": {"SessionTicket": "session-1"}}"#;
+const USER_DATA_OK: &str =
+    r#"{"code": 200, "status": "OK", "data": {"Data": {"readlogs": {"Value": "[101, 102]"}}}}"#;
+const INVALID_TICKET: &str = r#"{
+    "code": 400,
+    "status": "BadRequest",
+    "error": "InvalidSteamTicket",
+    "errorCode": 1010,
+    "errorMessage": "Steam API AuthenticateUserTicket error response",
+    "errorDetails": {"SteamTicket": ["Invalid ticket"]}
+}"#;
+const SERVICE_UNAVAILABLE: &str = r#"{
+    "code": 503,
+    "status": "ServiceUnavailable",
+    "error": "ServiceUnavailable",
+    "errorCode": 1123,
+    "errorMessage": "Try again later"
+}"#;
+const NOT_AUTHENTICATED: &str = r#"{
+    "code": 401,
+    "status": "Unauthorized",
+    "error": "NotAuthenticated",
+    "errorCode": 1074,
+    "errorMessage": "This API method does not allow anonymous callers."
+}"#;
+
+#[derive(Debug, Clone)]
+struct Request {
+    path: String,
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+/// Answers each request to `/Client/<api>` with the next response queued for that api and
+/// records every request it gets
+struct StandIn {
+    base_url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl StandIn {
+    fn start(responses: Vec<(&'static str, u16, &'static str)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let mut responses = responses;
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let request = read_request(&mut stream);
+                let api = request.path.trim_start_matches("/Client/").to_string();
+                recorded.lock().unwrap().push(request);
+
+                let (status, body) = match responses.iter().position(|(a, ..)| *a == api) {
+                    Some(i) => {
+                        let (_, status, body) = responses.remove(i);
+                        (status, body)
+                    }
+                    None => (404, "Not found"),
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+
+        StandIn { base_url, requests }
+    }
+
+    fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn client(&self) -> Client {
+        Client::new(ClientSettings {
+            base_url: self.base_url.clone(),
+            timeout: Duration::from_secs(5),
+            connect_timeout: Duration::from_secs(5),
+            retries: 1,
+            retry_delay: Duration::ZERO,
+        })
+        .unwrap()
+    }
+}
+
+fn read_request(stream: &mut impl Read) -> Request {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        match line.trim_end().split_once(": ") {
+            Some((name, value)) => {
+                headers.insert(name.to_ascii_lowercase(), value.to_string());
+            }
+            None => break,
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+
+    Request {
+        path,
+        headers,
+        body: String::from_utf8(body).unwrap(),
+    }
+}
+
+struct FakeTickets(Result<Vec<u8>, PlayFabError>);
+
+impl TicketProvider for FakeTickets {
+    type Ticket = Vec<u8>;
+
+    fn auth_ticket(&self) -> Result<Vec<u8>, PlayFabError> {
+        self.0.clone()
+    }
+}
+
+fn tickets() -> FakeTickets {
+    FakeTickets(Ok(vec![0x01, 0xAB]))
+}
+
+fn no_steam() -> FakeTickets {
+    FakeTickets(Err(PlayFabError::Steam("Steam isn't running".to_string())))
+}
+
+/// Where the session cache for the test called `name` in this process is saved
+fn session_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "gtfo-log-tracker-session-{name}-{}.json",
+        std::process::id()
+    ))
+}
+
+/// An empty session cache that's only used by the test called `name`
+fn sessions(name: &str) -> SessionCache {
+    let path = session_path(name);
+    let _ = std::fs::remove_file(&path);
+    SessionCache::new(path)
+}
+
+#[tokio::test]
+async fn logs_in_and_gets_read_logs() {
+    let server = StandIn::start(vec![
+        ("LoginWithSteam", 200, LOGIN_OK),
+        ("GetUserData", 200, USER_DATA_OK),
+    ]);
+
+    let ids = get_read_log_ids(
+        &server.client(),
+        &tickets(),
+        &sessions("logs_in_and_gets_read_logs"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(ids, vec![101, 102]);
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    let login: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+    assert_eq!(login["steamTicket"], "01AB");
+    assert_eq!(login["titleId"], "8f9");
+    assert_eq!(requests[1].path, "/Client/GetUserData");
+    assert_eq!(requests[1].headers["x-authorization"], "session-1");
+}
+
+#[tokio::test]
+async fn reports_login_error() {
+    let server = StandIn::start(vec![("LoginWithSteam", 400, INVALID_TICKET)]);
+
+    let error = get_read_log_ids(
+        &server.client(),
+        &tickets(),
+        &sessions("reports_login_error"),
+    )
+    .await
+    .unwrap_err();
+
+    match error {
+        PlayFabError::Api(details) => {
+            assert_eq!(details.error, "InvalidSteamTicket");
+            assert_eq!(details.error_code, 1010);
+            assert!(details.error_details.is_some());
+        }
+        e => panic!("Expected an API error, got {e:?}"),
+    }
+    assert_eq!(
+        server.requests().len(),
+        1,
+        "Shouldn't retry a rejected ticket"
+    );
+}
+
+#[tokio::test]
+async fn retries_user_data_when_unavailable() {
+    let server = StandIn::start(vec![
+        ("LoginWithSteam", 200, LOGIN_OK),
+        ("GetUserData", 503, SERVICE_UNAVAILABLE),
+        ("GetUserData", 200, USER_DATA_OK),
+    ]);
+
+    let ids = get_read_log_ids(
+        &server.client(),
+        &tickets(),
+        &sessions("retries_user_data_when_unavailable"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(ids, vec![101, 102]);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_retries() {
+    let server = StandIn::start(vec![
+        ("LoginWithSteam", 200, LOGIN_OK),
+        ("GetUserData", 503, SERVICE_UNAVAILABLE),
+        ("GetUserData", 503, SERVICE_UNAVAILABLE),
+    ]);
+
+    let error = get_read_log_ids(
+        &server.client(),
+        &tickets(),
+        &sessions("gives_up_after_retries"),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(error, PlayFabError::Api(d) if d.error == "ServiceUnavailable"));
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn reports_unexpected_response() {
+    let server = StandIn::start(vec![
+        ("LoginWithSteam", 502, "<html>Bad gateway</html>"),
+        ("LoginWithSteam", 502, "<html>Bad gateway</html>"),
+    ]);
+
+    let error = get_read_log_ids(
+        &server.client(),
+        &tickets(),
+        &sessions("reports_unexpected_response"),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(
+        error,
+        PlayFabError::InvalidResponse { status: 502, .. }
+    ));
+}
+
+#[tokio::test]
+async fn stops_if_there_is_no_ticket() {
+    let server = StandIn::start(Vec::new());
+
+    let error = get_read_log_ids(
+        &server.client(),
+        &no_steam(),
+        &sessions("stops_if_there_is_no_ticket"),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(error, PlayFabError::Steam(_)));
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn reuses_saved_session() {
+    let server = StandIn::start(vec![
+        ("LoginWithSteam", 200, LOGIN_OK),
+        ("GetUserData", 200, USER_DATA_OK),
+        ("GetUserData", 200, USER_DATA_OK),
+    ]);
+    let sessions = sessions("reuses_saved_session");
+
+    get_read_log_ids(&server.client(), &tickets(), &sessions)
+        .await
+        .unwrap();
+    let ids = get_read_log_ids(&server.client(), &no_steam(), &sessions)
+        .await
+        .unwrap();
+
+    assert_eq!(ids, vec![101, 102]);
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[2].path, "/Client/GetUserData");
+    assert_eq!(requests[2].headers["x-authorization"], "session-1");
+}
+
+#[tokio::test]
+async fn logs_in_again_when_session_is_rejected() {
+    let server = StandIn::start(vec![
+        ("LoginWithSteam", 200, LOGIN_OK),
+        ("GetUserData", 200, USER_DATA_OK),
+        ("GetUserData", 401, NOT_AUTHENTICATED),
+        ("LoginWithSteam", 200, LOGIN_OK),
+        ("GetUserData", 200, USER_DATA_OK),
+    ]);
+    let sessions = sessions("logs_in_again_when_session_is_rejected");
+
+    get_read_log_ids(&server.client(), &tickets(), &sessions)
+        .await
+        .unwrap();
+    let ids = get_read_log_ids(&server.client(), &tickets(), &sessions)
+        .await
+        .unwrap();
+
+    assert_eq!(ids, vec![101, 102]);
+    let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+        paths[2..],
+        [
+            "/Client/GetUserData",
+            "/Client/LoginWithSteam",
+            "/Client/GetUserData"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn keeps_saved_session_when_play_fab_is_unavailable() {
+    let server = StandIn::start(vec![
+        ("LoginWithSteam", 200, LOGIN_OK),
+        ("GetUserData", 200, USER_DATA_OK),
+        ("GetUserData", 503, SERVICE_UNAVAILABLE),
+        ("GetUserData", 503, SERVICE_UNAVAILABLE),
+        ("GetUserData", 200, USER_DATA_OK),
+    ]);
+    let sessions = sessions("keeps_saved_session_when_play_fab_is_unavailable");
+
+    get_read_log_ids(&server.client(), &tickets(), &sessions)
+        .await
+        .unwrap();
+    let error = get_read_log_ids(&server.client(), &no_steam(), &sessions)
+        .await
+        .unwrap_err();
+    let ids = get_read_log_ids(&server.client(), &no_steam(), &sessions)
+        .await
+        .unwrap();
+
+    assert!(matches!(error, PlayFabError::Api(d) if d.error == "ServiceUnavailable"));
+    assert_eq!(ids, vec![101, 102]);
+}
+
+#[tokio::test]
+async fn logs_in_again_when_session_has_expired() {
+    let server = StandIn::start(vec![
+        ("LoginWithSteam", 200, LOGIN_OK),
+        ("GetUserData", 200, USER_DATA_OK),
+    ]);
+    let name = "logs_in_again_when_session_has_expired";
+    let sessions = sessions(name);
+    std::fs::write(
+        session_path(name),
+        r#"{"ticket": "session-0", "expiresAt": "2020-01-01T00:00:00Z"}"#,
+    )
+    .unwrap();
+
+    get_read_log_ids(&server.client(), &tickets(), &sessions)
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].path, "/Client/LoginWithSteam");
+    assert_eq!(requests[1].headers["x-authorization"], "session-1");
+}